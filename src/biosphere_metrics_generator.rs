@@ -0,0 +1,168 @@
+use crate::game_manager::Property;
+use crate::organism_generator::{EnergySource, Habitat, Metabolism, Organism, Size, TrophicRole};
+use crate::planet_generator;
+use crate::planet_generator::Ocean;
+
+const EARTH_NET_PRIMARY_PRODUCTIVITY: f64 = 100.0; // in gigatonnes of carbon a year, with the whole surface green
+const WATER_OPTIMAL_TEMPERATURE: f64 = 300.0; // in kelvins; chemistry slows when colder, proteins fall apart when hotter
+const AMMONIA_OPTIMAL_TEMPERATURE: f64 = 220.0; // in kelvins; the middle of ammonia's liquid range
+const TEMPERATURE_TOLERANCE: f64 = 60.0; // in kelvins; how far from the optimum productivity falls by a factor of e
+const CHEMOSYNTHESIS_EFFICIENCY: f64 = 0.01; // vents and rocks yield far less than starlight
+const MINOR_HABITAT_COVERAGE: f64 = 0.05; // the fraction of a planet's productivity the air or the deep rock can host
+const SINGLE_CELL_TURNOVER_TIME: f64 = 0.1; // in years; plankton are eaten or die within weeks
+const MULTICELLULAR_TURNOVER_TIME: f64 = 10.0; // in years; woody producers hold their carbon for a long time
+const CONSUMER_BIOMASS_FRACTION: f64 = 0.05; // of producer biomass, carried by everything that eats
+
+// Biosphere-wide figures for a planet's living species, so worlds can be compared side by side
+#[derive(Clone)]
+pub struct BiosphereMetrics {
+    species_richness: usize,
+    net_primary_productivity: f64, // in gigatonnes of carbon a year
+    total_biomass: f64, // in gigatonnes of carbon
+    functional_groups: usize, // distinct pairings of trophic role and habitat
+    functional_diversity: f64, // Shannon index over functional groups
+    dominant_metabolism: Option<Metabolism>,
+}
+
+impl BiosphereMetrics {
+    pub fn new(planet: &planet_generator::Planet, organisms: &[&Organism]) -> BiosphereMetrics {
+        let (net_primary_productivity, producer_biomass) = Self::calculate_production(planet, organisms);
+        let has_consumers = organisms.iter().any(|organism| *organism.get_trophic_role() != TrophicRole::Producer);
+        let total_biomass = producer_biomass * if has_consumers { 1.0 + CONSUMER_BIOMASS_FRACTION } else { 1.0 };
+        let (functional_groups, functional_diversity) = Self::calculate_functional_diversity(organisms);
+
+        BiosphereMetrics {
+            species_richness: organisms.len(),
+            net_primary_productivity,
+            total_biomass,
+            functional_groups,
+            functional_diversity,
+            dominant_metabolism: Self::find_dominant_metabolism(organisms),
+        }
+    }
+
+    // Each habitat produces according to its share of the surface and the best producer living in
+    // it, and holds that carbon for as long as its producers live. Surface area grows with the
+    // square of radius, which at a fixed density goes with surface gravity.
+    fn calculate_production(planet: &planet_generator::Planet, organisms: &[&Organism]) -> (f64, f64) {
+        let area = planet.get_surface_gravity().powi(2);
+        let optimum = if *planet.get_ocean() == Ocean::Ammonia { AMMONIA_OPTIMAL_TEMPERATURE } else { WATER_OPTIMAL_TEMPERATURE };
+        let temperature = ((planet.get_temperature() as f64 - optimum) / TEMPERATURE_TOLERANCE).powi(2);
+        let potential = EARTH_NET_PRIMARY_PRODUCTIVITY * area * (-temperature).exp();
+        let ocean = planet.get_ocean_coverage();
+
+        let habitats = [
+            (Habitat::Pelagic, ocean),
+            (Habitat::Benthic, ocean),
+            (Habitat::Terrestrial, 1.0 - ocean),
+            (Habitat::Aerial, MINOR_HABITAT_COVERAGE),
+            (Habitat::Subsurface, MINOR_HABITAT_COVERAGE),
+        ];
+
+        let mut productivity = 0.0;
+        let mut biomass = 0.0;
+        for (habitat, coverage) in habitats {
+            let best = organisms.iter()
+                .filter(|organism| *organism.get_habitat() == habitat && *organism.get_trophic_role() == TrophicRole::Producer)
+                .map(|organism| {
+                    let efficiency = match organism.get_energy_source() {
+                        EnergySource::Phototroph => 1.0,
+                        EnergySource::Chemotroph => CHEMOSYNTHESIS_EFFICIENCY,
+                        EnergySource::Heterotroph => 0.0,
+                    };
+                    let turnover = match organism.get_size() {
+                        Size::SingleCell => SINGLE_CELL_TURNOVER_TIME,
+                        Size::MultiCellular => MULTICELLULAR_TURNOVER_TIME,
+                    };
+                    (efficiency, turnover)
+                })
+                .max_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+            if let Some((efficiency, turnover)) = best {
+                // Sea-floor and open-water producers share the same sunlit ocean
+                let share = if habitat == Habitat::Benthic { coverage / 2.0 } else { coverage };
+                let production = potential * share * efficiency;
+                productivity += production;
+                biomass += production * turnover;
+            }
+        }
+        (productivity, biomass)
+    }
+
+    fn calculate_functional_diversity(organisms: &[&Organism]) -> (usize, f64) {
+        let mut groups: Vec<(&TrophicRole, &Habitat, usize)> = Vec::new();
+        for organism in organisms {
+            let role = organism.get_trophic_role();
+            let habitat = organism.get_habitat();
+            match groups.iter_mut().find(|group| group.0 == role && group.1 == habitat) {
+                Some(group) => group.2 += 1,
+                None => groups.push((role, habitat, 1)),
+            }
+        }
+        let total = organisms.len() as f64;
+        let shannon = groups.iter()
+            .map(|group| {
+                let proportion = group.2 as f64 / total;
+                proportion * (1.0 / proportion).ln()
+            })
+            .sum();
+        (groups.len(), shannon)
+    }
+
+    // The metabolism most species share, with ties going to the oxygen breathers
+    fn find_dominant_metabolism(organisms: &[&Organism]) -> Option<Metabolism> {
+        if organisms.is_empty() {
+            return None;
+        }
+        let aerobic = organisms.iter().filter(|organism| *organism.get_metabolism() == Metabolism::Aerobic).count();
+        if aerobic * 2 >= organisms.len() { Some(Metabolism::Aerobic) } else { Some(Metabolism::Anaerobic) }
+    }
+
+    pub fn get_info(&self) -> String {
+        match &self.dominant_metabolism {
+            None => "Nothing lives here.".to_string(),
+            Some(metabolism) => format!("The biosphere holds {} species in {} functional groups (diversity \
+            index {:.2}), fixes {:.2} gigatonnes of carbon a year, and weighs {:.2} gigatonnes of carbon. \
+            Most species are {}.", self.species_richness, self.functional_groups, self.functional_diversity,
+                                        self.net_primary_productivity, self.total_biomass, metabolism),
+        }
+    }
+
+    pub fn get_species_richness(&self) -> usize {
+        self.species_richness
+    }
+
+    pub fn get_net_primary_productivity(&self) -> f64 {
+        self.net_primary_productivity
+    }
+
+    pub fn get_total_biomass(&self) -> f64 {
+        self.total_biomass
+    }
+
+    pub fn get_functional_groups(&self) -> usize {
+        self.functional_groups
+    }
+
+    pub fn get_functional_diversity(&self) -> f64 {
+        self.functional_diversity
+    }
+
+    pub fn get_dominant_metabolism(&self) -> Option<&Metabolism> {
+        self.dominant_metabolism.as_ref()
+    }
+}
+
+// The figures shared by a planet's own scene, its JSON and the export
+pub fn describe_metrics(metrics: &BiosphereMetrics) -> Vec<Property> {
+    let mut properties = vec![
+        Property::integer("Living species", metrics.get_species_richness() as i64),
+        Property::number("Net primary productivity (Gt C/yr)", metrics.get_net_primary_productivity()),
+        Property::number("Biomass (Gt C)", metrics.get_total_biomass()),
+        Property::integer("Functional groups", metrics.get_functional_groups() as i64),
+        Property::number("Functional diversity", metrics.get_functional_diversity()),
+    ];
+    if let Some(metabolism) = metrics.get_dominant_metabolism() {
+        properties.push(Property::text("Dominant metabolism", metabolism));
+    }
+    properties
+}
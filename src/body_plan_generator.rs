@@ -0,0 +1,376 @@
+use strum_macros::Display;
+use rand::prelude::*;
+
+use crate::organism_generator::{Habitat, Locomotion, Metabolism, Organism, Organization, Sense, Size,
+                                Symmetry, TrophicRole};
+use crate::planet_generator;
+
+const SMALLEST_CELL: f64 = 1e-6; // in metres
+const LARGEST_CELL: f64 = 1e-4;
+const SMALLEST_BODY: f64 = 1e-3; // for multicellular life
+const LARGEST_COLONY: f64 = 10.0; // under one gravity
+const LARGEST_AQUATIC_BODY: f64 = 30.0; // water bears the weight, so gravity doesn't limit it
+const LARGEST_LAND_BODY: f64 = 10.0; // under one gravity; legs must carry weight that grows with volume
+const LARGEST_FLYER: f64 = 2.0; // under one gravity and one atmosphere
+const ONE_ATMOSPHERE: f64 = 100000.0; // in pascals
+const LARGEST_EXOSKELETON: f64 = 0.5; // under one gravity; bigger shells can't be moulted safely
+const HIGH_GRAVITY: f64 = 1.1; // in multiples of the Earth's
+const LOW_GRAVITY: f64 = 0.6; // soft bodies can hold themselves up on land
+const MAXIMUM_SEGMENTS: f64 = 12.0; // under one gravity; heavy worlds fuse segments together
+const MINIMUM_RADIAL_ORDER: u8 = 4;
+const MAXIMUM_RADIAL_ORDER: u8 = 8;
+
+#[derive(Display, Clone, PartialEq)]
+pub enum Skeleton {
+    None,
+    CellWall,
+    Hydrostatic,
+    Exoskeleton,
+    Endoskeleton,
+}
+
+#[derive(Display, Clone, PartialEq)]
+pub enum Covering {
+    Membrane,
+    Mucus,
+    Cuticle,
+    Shell,
+    Scales,
+    Hide,
+    Fur,
+}
+
+#[derive(Display, Clone, PartialEq)]
+pub enum Appendage {
+    Flagella,
+    Cilia,
+    Fronds,
+    Tentacles,
+    Fins,
+    Legs,
+    Wings,
+    Claws,
+}
+
+#[derive(Display, Clone, PartialEq)]
+pub enum SensoryOrgan {
+    Eyespots,
+    Eyes,
+    Antennae,
+    Whiskers,
+    Electroreceptors,
+    Thermoreceptors,
+}
+
+// The shape of an organism's body. Lengths are in metres.
+#[derive(Clone)]
+pub struct BodyPlan {
+    length: f64,
+    segments: u8,
+    appendages: Vec<(Appendage, u8)>,
+    sensory_organs: Vec<(SensoryOrgan, u8)>,
+    skeleton: Skeleton,
+    covering: Covering,
+}
+
+// A featureless cell, standing in until the rest of an organism's traits are known
+impl Default for BodyPlan {
+    fn default() -> BodyPlan {
+        BodyPlan {
+            length: SMALLEST_CELL,
+            segments: 1,
+            appendages: Vec::new(),
+            sensory_organs: Vec::new(),
+            skeleton: Skeleton::None,
+            covering: Covering::Membrane,
+        }
+    }
+}
+
+impl BodyPlan {
+    // Builds a body to fit the organism's symmetry and way of life, sized and supported for the
+    // pull of its planet's gravity and, for flyers, the thickness of its air when it appears
+    pub fn new (planet: &planet_generator::Planet, time: u16, organism: &Organism, rng: &mut impl Rng) -> BodyPlan {
+        let gravity = planet.get_surface_gravity();
+        let air = planet.get_pressure_at(time) as f64 / ONE_ATMOSPHERE;
+
+        let length = Self::choose_length(organism, gravity, air, rng);
+        let segments = if *organism.get_symmetry() == Symmetry::Bilateral {
+            rng.gen_range(1..=((MAXIMUM_SEGMENTS / gravity).round() as u8).clamp(1, MAXIMUM_SEGMENTS as u8))
+        } else {
+            1
+        };
+        let radial_order = rng.gen_range(MINIMUM_RADIAL_ORDER..=MAXIMUM_RADIAL_ORDER);
+        let appendages = Self::choose_appendages(organism, segments, radial_order, gravity, air, rng);
+        let sensory_organs = Self::choose_sensory_organs(organism, radial_order, rng);
+        let skeleton = Self::choose_skeleton(organism, length, gravity, rng);
+        let covering = Self::choose_covering(organism, &skeleton, rng);
+
+        BodyPlan {
+            length,
+            segments,
+            appendages,
+            sensory_organs,
+            skeleton,
+            covering,
+        }
+    }
+
+    fn is_aquatic (habitat: &Habitat) -> bool {
+        *habitat == Habitat::Pelagic || *habitat == Habitat::Benthic
+    }
+
+    // Spread evenly across orders of magnitude between the smallest and largest bodies possible
+    fn choose_length (organism: &Organism, gravity: f64, air: f64, rng: &mut impl Rng) -> f64 {
+        let aquatic = Self::is_aquatic(organism.get_habitat());
+        let (smallest, largest) = match (organism.get_size(), organism.get_organization()) {
+            (Size::SingleCell, _) => (SMALLEST_CELL, LARGEST_CELL),
+            (Size::MultiCellular, _) if aquatic => (SMALLEST_BODY, LARGEST_AQUATIC_BODY),
+            (Size::MultiCellular, Organization::Modular) => (SMALLEST_BODY, LARGEST_COLONY / gravity),
+            (Size::MultiCellular, Organization::Unitary) => match organism.get_habitat() {
+                Habitat::Aerial => (SMALLEST_BODY, LARGEST_FLYER * air.sqrt() / gravity),
+                _ => (SMALLEST_BODY, LARGEST_LAND_BODY / gravity),
+            },
+        };
+        let largest = largest.max(smallest * 10.0);
+        10_f64.powf(rng.gen_range(smallest.log10()..=largest.log10()))
+    }
+
+    // Bilateral bodies carry their limbs in pairs and radial bodies in rings; heavy worlds need
+    // more legs to spread the load, and thin air needs more wing to stay aloft
+    fn choose_appendages (organism: &Organism, segments: u8, radial_order: u8, gravity: f64, air: f64,
+                          rng: &mut impl Rng) -> Vec<(Appendage, u8)> {
+        let producer = *organism.get_trophic_role() == TrophicRole::Producer;
+        let mut appendages = Vec::new();
+
+        match (organism.get_size(), organism.get_symmetry()) {
+            (Size::SingleCell, symmetry) => {
+                if *organism.get_locomotion() == Locomotion::Flagella {
+                    if *symmetry == Symmetry::Spherical {
+                        appendages.push((Appendage::Cilia, rng.gen_range(20..=200)));
+                    } else {
+                        appendages.push((Appendage::Flagella, rng.gen_range(1..=4)));
+                    }
+                }
+            }
+            (Size::MultiCellular, Symmetry::Radial) => {
+                let appendage = match organism.get_locomotion() {
+                    Locomotion::Walking | Locomotion::Crawling => Some(Appendage::Legs),
+                    Locomotion::Flying => Some(Appendage::Wings),
+                    Locomotion::Burrowing => None,
+                    _ if producer => Some(Appendage::Fronds),
+                    _ => Some(Appendage::Tentacles),
+                };
+                if let Some(appendage) = appendage {
+                    appendages.push((appendage, radial_order));
+                }
+            }
+            (Size::MultiCellular, Symmetry::Bilateral) => match organism.get_locomotion() {
+                Locomotion::Walking => {
+                    let extra = if gravity > HIGH_GRAVITY { 1 } else { 0 };
+                    appendages.push((Appendage::Legs, 2 * (rng.gen_range(1..=3) + extra)));
+                }
+                Locomotion::Crawling => {
+                    if rng.gen_bool(0.5) {
+                        appendages.push((Appendage::Legs, 2 * segments));
+                    }
+                }
+                Locomotion::Swimming => appendages.push((Appendage::Fins, 2 * rng.gen_range(1..=3))),
+                Locomotion::Flying => {
+                    let pairs = if air < 1.0 || rng.gen_bool(0.25) { 2 } else { 1 };
+                    appendages.push((Appendage::Wings, 2 * pairs));
+                    appendages.push((Appendage::Legs, 2));
+                }
+                Locomotion::Burrowing => {
+                    if rng.gen_bool(0.5) {
+                        appendages.push((Appendage::Claws, 2));
+                    }
+                }
+                _ => appendages.push((Appendage::Fronds, 2 * rng.gen_range(1..=4))),
+            },
+            // Irregular colonies sprawl with no set number of parts
+            (Size::MultiCellular, _) => {
+                let appendage = if producer { Appendage::Fronds } else { Appendage::Tentacles };
+                appendages.push((appendage, rng.gen_range(3..=12)));
+            }
+        }
+
+        let hunter = *organism.get_trophic_role() == TrophicRole::Predator
+            && *organism.get_locomotion() != Locomotion::Sessile;
+        if hunter && *organism.get_size() == Size::MultiCellular
+            && appendages.iter().all(|appendage| appendage.0 != Appendage::Claws) {
+            appendages.push((Appendage::Claws, 2));
+        }
+        appendages
+    }
+
+    // Single cells sense through their membrane, with at most a patch of pigment for light
+    fn choose_sensory_organs (organism: &Organism, radial_order: u8, rng: &mut impl Rng) -> Vec<(SensoryOrgan, u8)> {
+        let mut organs = Vec::new();
+        let single_cell = *organism.get_size() == Size::SingleCell;
+        let bilateral = *organism.get_symmetry() == Symmetry::Bilateral;
+        let unitary = *organism.get_organization() == Organization::Unitary;
+
+        for sense in organism.get_senses() {
+            let organ = match sense {
+                Sense::Photoreception if single_cell => (SensoryOrgan::Eyespots, 1),
+                Sense::Photoreception if bilateral => (SensoryOrgan::Eyes, 2 * rng.gen_range(1..=2)),
+                Sense::Photoreception if unitary => (SensoryOrgan::Eyes, radial_order),
+                Sense::Photoreception => (SensoryOrgan::Eyespots, rng.gen_range(3..=12)),
+                _ if single_cell => continue,
+                Sense::Chemoreception if bilateral && *organism.get_locomotion() != Locomotion::Sessile => {
+                    (SensoryOrgan::Antennae, 2)
+                }
+                Sense::Chemoreception => continue,
+                Sense::Mechanoreception => (SensoryOrgan::Whiskers, 2 * rng.gen_range(2..=6)),
+                Sense::Electroreception => (SensoryOrgan::Electroreceptors, rng.gen_range(2..=20)),
+                Sense::Thermoreception => (SensoryOrgan::Thermoreceptors, 2),
+            };
+            organs.push(organ);
+        }
+        organs
+    }
+
+    // Water carries the weight of aquatic bodies. On land, exoskeletons only work for small bodies,
+    // and only low gravity lets soft bodies stand up without a skeleton at all.
+    fn choose_skeleton (organism: &Organism, length: f64, gravity: f64, rng: &mut impl Rng) -> Skeleton {
+        match (organism.get_size(), organism.get_organization()) {
+            (Size::SingleCell, _) => match organism.get_locomotion() {
+                Locomotion::Flagella => Skeleton::None,
+                _ => Skeleton::CellWall,
+            },
+            (Size::MultiCellular, Organization::Modular) => {
+                if rng.gen_bool(0.5) { Skeleton::Exoskeleton } else { Skeleton::Hydrostatic }
+            }
+            (Size::MultiCellular, Organization::Unitary) => {
+                let mut skeletons = vec![Skeleton::Endoskeleton];
+                let aquatic = Self::is_aquatic(organism.get_habitat());
+                if aquatic || length <= LARGEST_EXOSKELETON / gravity {
+                    skeletons.push(Skeleton::Exoskeleton);
+                }
+                if aquatic || *organism.get_locomotion() == Locomotion::Burrowing || gravity < LOW_GRAVITY {
+                    skeletons.push(Skeleton::Hydrostatic);
+                }
+                skeletons.choose(rng).unwrap().clone()
+            }
+        }
+    }
+
+    // Fur keeps a warm body warm, which takes the fast metabolism oxygen allows
+    fn choose_covering (organism: &Organism, skeleton: &Skeleton, rng: &mut impl Rng) -> Covering {
+        match skeleton {
+            Skeleton::None | Skeleton::CellWall => Covering::Membrane,
+            Skeleton::Hydrostatic => Covering::Mucus,
+            Skeleton::Exoskeleton => {
+                if *organism.get_locomotion() == Locomotion::Sessile { Covering::Shell } else { Covering::Cuticle }
+            }
+            Skeleton::Endoskeleton => {
+                let mut coverings = vec![Covering::Scales, Covering::Hide];
+                if !Self::is_aquatic(organism.get_habitat()) && *organism.get_metabolism() == Metabolism::Aerobic {
+                    coverings.push(Covering::Fur);
+                }
+                coverings.choose(rng).unwrap().clone()
+            }
+        }
+    }
+
+    fn format_length (length: f64) -> String {
+        if length < 1e-3 {
+            format!("{:.0} µm", length * 1e6)
+        } else if length < 1e-2 {
+            format!("{:.1} mm", length * 1e3)
+        } else if length < 1.0 {
+            format!("{:.1} cm", length * 1e2)
+        } else {
+            format!("{:.1} m", length)
+        }
+    }
+
+    fn format_parts (parts: Vec<String>) -> String {
+        match parts.len() {
+            0 => String::new(),
+            1 => parts[0].clone(),
+            n => format!("{} and {}", parts[..n - 1].join(", "), parts[n - 1]),
+        }
+    }
+
+    fn format_count (count: u8, name: String) -> String {
+        let name = name.to_lowercase();
+        if count != 1 {
+            return format!("{} {}", count, name);
+        }
+        let singular = match name.as_str() {
+            "flagella" => "flagellum".to_string(),
+            "cilia" => "cilium".to_string(),
+            "antennae" => "antenna".to_string(),
+            _ => name.trim_end_matches('s').to_string(),
+        };
+        format!("a single {}", singular)
+    }
+
+    pub fn get_description(&self, symmetry: &Symmetry) -> String {
+        let shape = match symmetry {
+            Symmetry::Asymmetrical => "irregular",
+            Symmetry::Spherical => "spherical",
+            Symmetry::Radial => "radially symmetrical",
+            Symmetry::Bilateral => "bilaterally symmetrical",
+        };
+        let segments = if self.segments > 1 {
+            format!(" of {} segments", self.segments)
+        } else {
+            String::new()
+        };
+        let skeleton = match self.skeleton {
+            Skeleton::None => "has no skeleton",
+            Skeleton::CellWall => "is stiffened by a cell wall",
+            Skeleton::Hydrostatic => "holds its shape with fluid pressure",
+            Skeleton::Exoskeleton => "is supported by an exoskeleton",
+            Skeleton::Endoskeleton => "is supported by an internal skeleton",
+        };
+        let covering = match self.covering {
+            Covering::Membrane => "a thin membrane",
+            Covering::Mucus => "slime",
+            Covering::Cuticle => "a tough cuticle",
+            Covering::Shell => "a hard shell",
+            Covering::Scales => "scales",
+            Covering::Hide => "a leathery hide",
+            Covering::Fur => "fur",
+        };
+
+        let appendages: Vec<String> = self.appendages.iter()
+            .map(|(appendage, count)| Self::format_count(*count, appendage.to_string()))
+            .collect();
+        let organs: Vec<String> = self.sensory_organs.iter()
+            .map(|(organ, count)| Self::format_count(*count, organ.to_string()))
+            .collect();
+        let appendages = if appendages.is_empty() { "no appendages".to_string() } else { Self::format_parts(appendages) };
+        let organs = if organs.is_empty() { "no sense organs".to_string() } else { Self::format_parts(organs) };
+
+        format!("Its {} {} body{} {} and is covered in {}. It has {}, and {}.",
+                Self::format_length(self.length), shape, segments, skeleton, covering, appendages, organs)
+    }
+
+    pub fn get_length(&self) -> f64 {
+        self.length
+    }
+
+    pub fn get_segments(&self) -> u8 {
+        self.segments
+    }
+
+    pub fn get_appendages(&self) -> &Vec<(Appendage, u8)> {
+        &self.appendages
+    }
+
+    pub fn get_sensory_organs(&self) -> &Vec<(SensoryOrgan, u8)> {
+        &self.sensory_organs
+    }
+
+    pub fn get_skeleton(&self) -> &Skeleton {
+        &self.skeleton
+    }
+
+    pub fn get_covering(&self) -> &Covering {
+        &self.covering
+    }
+}
@@ -0,0 +1,223 @@
+use rand::prelude::*;
+use strum_macros::Display;
+
+use crate::game_manager::Property;
+use crate::organism_generator::{EnergySource, Habitat, Metabolism, Organism, Size};
+use crate::phylogeny_generator::Phylogeny;
+use crate::planet_generator;
+use crate::planet_generator::Ocean;
+use crate::planetary_environment_generator::BIOSPHERE_TIME_STEP;
+
+// Rates are chances per million years; severities are the fraction of species an event would
+// wipe out among the most exposed
+const IMPACT_RATE: f64 = 0.0005;
+const EARLY_BOMBARDMENT_FACTOR: f64 = 20.0; // young systems are still full of debris
+const BOMBARDMENT_DECAY_TIME: f64 = 500.0; // in millions of years
+const IMPACT_SEVERITY: (f64, f64) = (0.1, 0.75);
+const SUPERVOLCANISM_RATE: f64 = 0.0004; // on the most geologically active worlds
+const MASS_FOR_FULL_VOLCANISM: f64 = 10000.0;
+const SUPERVOLCANISM_SEVERITY: (f64, f64) = (0.1, 0.5);
+const STELLAR_EXPLOSION_RATE: f64 = 0.0002; // nearby supernovae and gamma-ray bursts together
+const GAMMA_RAY_BURST_CHANCE: f64 = 0.2; // of stellar explosions
+const STELLAR_EXPLOSION_SEVERITY: (f64, f64) = (0.2, 0.6);
+const FLARE_RATE: f64 = 0.0001;
+const RED_DWARF_FLARE_FACTOR: f64 = 10.0; // small, convective stars flare far more often
+const RED_DWARF_MASS: f64 = 0.5; // in solar masses
+const FLARE_SEVERITY: (f64, f64) = (0.05, 0.3);
+const MAGNETOSPHERE_SHIELDING: f64 = 0.5; // fraction of a flare's harm a magnetic field turns away
+const RUNAWAY_GREENHOUSE_RATE: f64 = 0.0001;
+const RUNAWAY_GREENHOUSE_SEVERITY: (f64, f64) = (0.3, 0.8);
+const RUNAWAY_GREENHOUSE_THRESHOLD: f64 = 0.5; // of the way from the solvent's freezing point to its boiling point
+const STEP_SEED_MULTIPLIER: u64 = 1_000_003;
+const CATASTROPHE_SEED_OFFSET: u64 = 86028121; // keeps catastrophes independent of other rolls
+
+#[derive(Display, Clone, PartialEq)]
+pub enum CatastropheKind {
+    AsteroidImpact,
+    Supervolcanism,
+    Supernova,
+    GammaRayBurst,
+    StellarFlare,
+    RunawayGreenhouse,
+}
+
+// A disaster in a planet's history and the toll it took. Times are the star's age in millions
+// of years.
+#[derive(Clone)]
+pub struct Catastrophe {
+    kind: CatastropheKind,
+    time: u16,
+    species_before: usize,
+    species_lost: usize,
+}
+
+impl Catastrophe {
+    // Rolls for the events of one time step of the planet's history. Each step is seeded from the
+    // planet and the step, so the planet and its biosphere always see the same events. The rolls
+    // go on afterwards to decide who an event strikes.
+    pub fn roll_step(planet: &planet_generator::Planet, step: u16) -> (Vec<(CatastropheKind, f64)>, StdRng) {
+        let mut rng = StdRng::seed_from_u64(planet.get_seed().wrapping_add(CATASTROPHE_SEED_OFFSET)
+            .wrapping_mul(STEP_SEED_MULTIPLIER).wrapping_add(step as u64));
+        let events = Self::roll(planet, step * BIOSPHERE_TIME_STEP, BIOSPHERE_TIME_STEP, &mut rng);
+        (events, rng)
+    }
+
+    // Rolls for each kind of event over a stretch of the planet's history, worst first when more
+    // than one strikes
+    pub fn roll(planet: &planet_generator::Planet, time: u16, duration: u16, rng: &mut impl Rng) -> Vec<(CatastropheKind, f64)> {
+        let duration = duration as f64;
+        let bombardment = 1.0 + EARLY_BOMBARDMENT_FACTOR * (-(time as f64) / BOMBARDMENT_DECAY_TIME).exp();
+        let volcanism = (planet.get_mass() as f64 / MASS_FOR_FULL_VOLCANISM).min(1.0);
+        let flares = if planet.get_star_mass() < RED_DWARF_MASS { RED_DWARF_FLARE_FACTOR } else { 1.0 };
+        let shielding = if planet.has_magnetic_field_at(time) { 1.0 - MAGNETOSPHERE_SHIELDING } else { 1.0 };
+        // Only an ocean warm enough to fill the air with vapour can tip into a runaway greenhouse,
+        // the likelier the nearer it is to boiling. The climate is the one of the time, so that
+        // history doesn't shift as the star ages.
+        let (temperature, ocean) = planet.get_climate_at(time);
+        let (freezing, boiling) = match ocean {
+            Ocean::Water => (273.0, 373.0),
+            Ocean::Ammonia => (196.0, 240.0),
+            Ocean::None => (0.0, 0.0),
+        };
+        let vapour = if boiling > freezing {
+            let warmth = (temperature as f64 - freezing) / (boiling - freezing);
+            ((warmth - RUNAWAY_GREENHOUSE_THRESHOLD) / (1.0 - RUNAWAY_GREENHOUSE_THRESHOLD)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let stellar_explosion = if rng.gen_bool(GAMMA_RAY_BURST_CHANCE) {
+            CatastropheKind::GammaRayBurst
+        } else {
+            CatastropheKind::Supernova
+        };
+        let candidates = [
+            (CatastropheKind::AsteroidImpact, IMPACT_RATE * bombardment, IMPACT_SEVERITY, 1.0),
+            (CatastropheKind::Supervolcanism, SUPERVOLCANISM_RATE * volcanism, SUPERVOLCANISM_SEVERITY, 1.0),
+            (stellar_explosion, STELLAR_EXPLOSION_RATE, STELLAR_EXPLOSION_SEVERITY, 1.0),
+            (CatastropheKind::StellarFlare, FLARE_RATE * flares, FLARE_SEVERITY, shielding),
+            (CatastropheKind::RunawayGreenhouse, RUNAWAY_GREENHOUSE_RATE * vapour, RUNAWAY_GREENHOUSE_SEVERITY, 1.0),
+        ];
+
+        let mut events: Vec<(CatastropheKind, f64)> = candidates.into_iter()
+            .filter_map(|(kind, rate, severity, factor)| {
+                let chance = 1.0 - (-rate * duration).exp();
+                let strikes = rng.gen_bool(chance);
+                let severity = rng.gen_range(severity.0..=severity.1) * factor;
+                if strikes { Some((kind, severity)) } else { None }
+            })
+            .collect();
+        events.sort_by(|a, b| b.1.total_cmp(&a.1));
+        events
+    }
+
+    // Kills off species in proportion to how exposed they are to this kind of event, always
+    // sparing at least one
+    pub fn strike(kind: CatastropheKind, severity: f64, phylogeny: &mut Phylogeny, time: u16,
+                  rng: &mut impl Rng) -> Catastrophe {
+        let living = phylogeny.get_living();
+        let exposure: Vec<f64> = living.iter()
+            .map(|&lineage| Self::calculate_exposure(&kind, phylogeny.get_lineage(lineage).unwrap().get_organism()))
+            .collect();
+        let average_exposure = exposure.iter().sum::<f64>() / exposure.len().max(1) as f64;
+        let losses = ((severity * average_exposure).min(1.0) * living.len() as f64).round() as usize;
+        let losses = losses.min(living.len().saturating_sub(1));
+
+        let victims: Vec<usize> = (0..living.len()).collect::<Vec<usize>>()
+            .choose_multiple_weighted(rng, losses, |&i| exposure[i])
+            .map(|victims| victims.copied().collect())
+            .unwrap_or_default();
+        for &victim in &victims {
+            phylogeny.go_extinct(living[victim], time);
+        }
+
+        Catastrophe { kind, time, species_before: living.len(), species_lost: victims.len() }
+    }
+
+    // A runaway greenhouse boils the ocean away, and nothing survives it
+    pub fn sterilize(kind: CatastropheKind, phylogeny: &mut Phylogeny, time: u16) -> Catastrophe {
+        let living = phylogeny.get_living();
+        for &lineage in &living {
+            phylogeny.go_extinct(lineage, time);
+        }
+        Catastrophe { kind, time, species_before: living.len(), species_lost: living.len() }
+    }
+
+    // Relative to an average species; what shelters a species depends on the disaster
+    fn calculate_exposure(kind: &CatastropheKind, organism: &Organism) -> f64 {
+        let large = *organism.get_size() == Size::MultiCellular;
+        let habitat = organism.get_habitat();
+        let surface = match habitat {
+            Habitat::Terrestrial | Habitat::Aerial => 1.5,
+            Habitat::Pelagic => 0.7,
+            Habitat::Benthic => 0.3,
+            Habitat::Subsurface => 0.1,
+        };
+        let phototroph = *organism.get_energy_source() == EnergySource::Phototroph;
+
+        match kind {
+            // Big animals starve in the impact winter; the deep and the underground ride it out
+            CatastropheKind::AsteroidImpact => {
+                let shelter = match habitat {
+                    Habitat::Subsurface => 0.3,
+                    Habitat::Benthic => 0.7,
+                    _ => 1.0,
+                };
+                shelter * if large { 2.0 } else { 1.0 }
+            }
+            // Ash blots out the sun and the air turns foul
+            CatastropheKind::Supervolcanism => {
+                let mut exposure = 1.0;
+                if phototroph { exposure *= 1.5; }
+                if *organism.get_metabolism() == Metabolism::Aerobic { exposure *= 1.5; }
+                if *organism.get_energy_source() == EnergySource::Chemotroph { exposure *= 0.5; }
+                exposure
+            }
+            // Radiation strips the ozone and sterilizes the surface
+            CatastropheKind::Supernova | CatastropheKind::GammaRayBurst | CatastropheKind::StellarFlare => {
+                surface * if phototroph { 1.5 } else { 1.0 }
+            }
+            CatastropheKind::RunawayGreenhouse => {
+                let shelter = if *habitat == Habitat::Subsurface { 0.5 } else { 1.0 };
+                shelter * if large { 2.0 } else { 1.0 }
+            }
+        }
+    }
+
+    pub fn get_info(&self, present: u16) -> String {
+        if self.species_lost == 0 {
+            return format!("{} million years ago: {} struck, but every species survived.",
+                           present - self.time, self.kind);
+        }
+        format!("{} million years ago: {} wiped out {:.0}% of species ({} of {}).",
+                present - self.time, self.kind, self.get_fraction_lost() * 100.0,
+                self.species_lost, self.species_before)
+    }
+
+    pub fn get_kind(&self) -> &CatastropheKind {
+        &self.kind
+    }
+
+    pub fn get_time(&self) -> u16 {
+        self.time
+    }
+
+    pub fn get_species_lost(&self) -> usize {
+        self.species_lost
+    }
+
+    pub fn get_fraction_lost(&self) -> f64 {
+        if self.species_before == 0 { 0.0 } else { self.species_lost as f64 / self.species_before as f64 }
+    }
+}
+
+// One event of a planet's timeline, as the timeline command and the export lay it out
+pub fn describe_catastrophe(catastrophe: &Catastrophe, present: u16) -> Vec<Property> {
+    vec![
+        Property::text("Kind", catastrophe.get_kind()),
+        Property::integer("Star age (Myr)", catastrophe.get_time()),
+        Property::integer("Million years ago", present - catastrophe.get_time()),
+        Property::integer("Species lost", catastrophe.get_species_lost() as i64),
+        Property::number("Fraction lost", catastrophe.get_fraction_lost()),
+    ]
+}
@@ -0,0 +1,133 @@
+use rand::prelude::*;
+use strum_macros::Display;
+
+use crate::phylogeny_generator::Phylogeny;
+use crate::planet_generator;
+
+const SAPIENCE_TIME: u16 = 3500; // in millions of years after the origin of life
+const SAPIENCE_MINIMUM_RICHNESS: usize = 5; // living species; minds evolve in rich ecosystems
+const MEAN_TIME_TO_SAPIENCE: f64 = 500.0; // in millions of years, for an eligible species
+const MEAN_CIVILIZATION_LIFETIME: f64 = 1000.0; // in millions of years
+const CIVILIZATION_SEED_OFFSET: u64 = 49979687; // keeps civilizations independent of other rolls
+const LINEAGE_SEED_MULTIPLIER: u64 = 1_000_003;
+const KARDASHEV_AT_SAPIENCE: f64 = 0.5; // fire, farming and cities come quickly on this timescale
+const KARDASHEV_PER_DECADE_OF_AGE: f64 = 0.5; // per tenfold increase in age, in millions of years
+const KARDASHEV_MAXIMUM: f64 = 3.0; // harnessing a whole galaxy
+
+// Each technosignature appears once a civilization reaches the given Kardashev level, and can be
+// seen from the given distance in light years at that level. Each further level brightens it
+// tenfold, which carries it the square root of ten times further.
+const TECHNOSIGNATURES: [(Technosignature, f64, f64); 4] = [
+    (Technosignature::AtmosphericPollutants, 0.6, 30.0),
+    (Technosignature::ArtificialLight, 0.7, 10.0),
+    (Technosignature::RadioLeakage, 0.7, 100.0),
+    (Technosignature::InfraredExcess, 2.0, 1000.0), // waste heat from structures around the star
+];
+
+#[derive(Display, Clone, PartialEq)]
+pub enum Technosignature {
+    AtmosphericPollutants,
+    ArtificialLight,
+    RadioLeakage,
+    InfraredExcess,
+}
+
+// A tool-using species and the civilization it has built. Times are the star's age in millions of
+// years.
+#[derive(Clone)]
+pub struct Civilization {
+    species: usize, // lineage in the planet's phylogeny
+    name: String,
+    origin: u16,
+    collapse: u16,
+    present: u16,
+}
+
+impl Civilization {
+    // Cheap enough to check every planet in the galaxy before building its biosphere
+    pub fn could_emerge(planet: &planet_generator::Planet) -> bool {
+        planet.get_origin_of_life()
+            .is_some_and(|origin| planet.get_age().saturating_sub(origin) >= SAPIENCE_TIME)
+    }
+
+    // Every living species able to use tools waits a random time for its first tools; the one
+    // that gets there first, if any have by now, founds the planet's civilization. Each species'
+    // rolls are seeded from the planet and the lineage, so they stay the same as time passes.
+    pub fn emerge(planet: &planet_generator::Planet, phylogeny: &Phylogeny) -> Option<Civilization> {
+        let origin_of_life = planet.get_origin_of_life()?;
+        let living = phylogeny.get_living();
+        if !Self::could_emerge(planet) || living.len() < SAPIENCE_MINIMUM_RICHNESS {
+            return None;
+        }
+
+        living.into_iter()
+            .filter_map(|index| {
+                let lineage = phylogeny.get_lineage(index)?;
+                if !lineage.get_organism().can_use_tools() {
+                    return None;
+                }
+                let mut rng = StdRng::seed_from_u64(planet.get_seed().wrapping_add(CIVILIZATION_SEED_OFFSET)
+                    .wrapping_mul(LINEAGE_SEED_MULTIPLIER).wrapping_add(index as u64));
+                let earliest = lineage.get_origin().max(origin_of_life + SAPIENCE_TIME);
+                let wait = -rng.gen_range(f64::EPSILON..1.0_f64).ln() * MEAN_TIME_TO_SAPIENCE;
+                let lifetime = -rng.gen_range(f64::EPSILON..1.0_f64).ln() * MEAN_CIVILIZATION_LIFETIME;
+                let origin = earliest as f64 + wait;
+                if origin > planet.get_age() as f64 {
+                    return None;
+                }
+                Some(Civilization {
+                    species: index,
+                    name: lineage.get_organism().get_name().clone(),
+                    origin: origin as u16,
+                    collapse: (origin + lifetime).min(u16::MAX as f64) as u16,
+                    present: planet.get_age(),
+                })
+            })
+            .min_by_key(|civilization| civilization.origin)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.present < self.collapse
+    }
+
+    // A Kardashev-like scale that climbs with the logarithm of the civilization's age, falling
+    // back to nothing once it collapses
+    pub fn get_level(&self) -> f64 {
+        if !self.is_active() {
+            return 0.0;
+        }
+        let age = (self.present - self.origin) as f64;
+        (KARDASHEV_AT_SAPIENCE + KARDASHEV_PER_DECADE_OF_AGE * (1.0 + age).log10()).min(KARDASHEV_MAXIMUM)
+    }
+
+    // The technosignatures the civilization gives off and how far away each can be detected,
+    // in light years
+    pub fn get_technosignatures(&self) -> Vec<(Technosignature, f64)> {
+        let level = self.get_level();
+        TECHNOSIGNATURES.iter()
+            .filter(|signature| level >= signature.1)
+            .map(|signature| (signature.0.clone(), signature.2 * 10_f64.powf((level - signature.1) / 2.0)))
+            .collect()
+    }
+
+    pub fn get_info(&self) -> String {
+        if !self.is_active() {
+            return format!("{} built a civilization {} million years ago that collapsed {} million \
+            years ago; only ruins remain.", self.name, self.present - self.origin, self.present - self.collapse);
+        }
+        let signatures: Vec<String> = self.get_technosignatures().iter()
+            .map(|(signature, range)| format!("{} (detectable out to {:.0} light years)", signature, range))
+            .collect();
+        let signatures = if signatures.is_empty() { "nothing detectable".to_string() } else { signatures.join(", ") };
+        format!("{} took up tools {} million years ago and has built a Kardashev level {:.2} \
+        civilization. It gives off {}.", self.name, self.present - self.origin, self.get_level(), signatures)
+    }
+
+    pub fn get_species(&self) -> usize {
+        self.species
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+}
@@ -0,0 +1,231 @@
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+
+// Every command the player can type: its name, how it is used, and what it does
+const COMMANDS: [(&str, &str, &str); 27] = [
+    ("info", "INFO <n>", "Describe place or organism n in the current scene. A bare number does the same."),
+    ("explore", "EXPLORE <n>", "Step into place n in the current scene."),
+    ("leave", "LEAVE", "Step back out to the scene that holds this one."),
+    ("back", "BACK", "Return to wherever you just were."),
+    ("goto", "GOTO <path>", "Jump straight to a path such as /star/42/planet/3/moon/0."),
+    ("list", "LIST [filter]", "List the places or organisms here, only those matching the filter if one is given."),
+    ("find", "FIND <habitable|life>", "Search the whole galaxy for planets that could hold life, or that do."),
+    ("compare", "COMPARE <n> <m>", "Set two places or organisms in the current scene side by side."),
+    ("advance", "ADVANCE <millions of years>", "Fast-forward time across the galaxy."),
+    ("tree", "TREE", "Show a living world's tree of life."),
+    ("clade", "CLADE <n>", "Inspect lineage n in the tree of life."),
+    ("newick", "NEWICK", "Export the tree of life in Newick format."),
+    ("web", "WEB", "Show who eats whom."),
+    ("remove", "REMOVE <n>", "Take species n out of the food web and watch what happens."),
+    ("draw", "DRAW", "Save a picture of every living species to the field guide."),
+    ("map", "MAP", "Open a window onto the galaxy, or the current star's system. Click a star to travel there."),
+    ("inspect", "INSPECT", "Open a window with every measurement of the current scene, and a tree to move around in."),
+    ("scan", "SCAN", "Listen from the current star for the technosignatures of civilizations around its neighbours."),
+    ("timeline", "TIMELINE", "List the catastrophes that have struck a living world."),
+    ("note", "NOTE <text>", "Jot something down in your journal."),
+    ("journal", "JOURNAL", "Read your discoveries and notes."),
+    ("save", "SAVE", "Save your game."),
+    ("load", "LOAD", "Pick up your saved game."),
+    ("history", "HISTORY", "Show the commands you have typed."),
+    ("help", "HELP [command]", "Show every command, or explain one."),
+    ("quit", "QUIT", "End the game. EXIT does the same."),
+    ("exit", "EXIT", "End the game."),
+];
+const SEARCHES: [&str; 2] = ["habitable", "life"];
+const PATH_LEVELS: [&str; 5] = ["star", "planet", "moon", "region", "organism"];
+
+pub enum Search {
+    Habitable,
+    Life,
+}
+
+pub enum Command {
+    Info(usize),
+    Explore(usize),
+    Leave,
+    Back,
+    Goto(String),
+    List(Option<String>),
+    Find(Search),
+    Compare(usize, usize),
+    Advance(u16),
+    Tree,
+    Clade(usize),
+    Newick,
+    Web,
+    Remove(usize),
+    Draw,
+    Map,
+    Inspect,
+    Scan,
+    Timeline,
+    Note(String),
+    Journal,
+    Save,
+    Load,
+    History,
+    Help(Option<String>),
+    Exit,
+}
+
+// Reads a line as a command name followed by its arguments. Names are case-insensitive; anything
+// free-form, like a note, keeps the player's own capitalisation.
+pub fn parse(line: &str) -> Result<Command, String> {
+    let line = line.trim();
+    let (name, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+    let arguments: Vec<&str> = rest.split_whitespace().collect();
+    let name = name.to_lowercase();
+
+    if let Ok(index) = name.parse::<usize>() {
+        return Ok(Command::Info(index));
+    }
+
+    let command = match name.as_str() {
+        "info" => Command::Info(parse_number(&name, &arguments, 0)?),
+        "explore" => Command::Explore(parse_number(&name, &arguments, 0)?),
+        "leave" => Command::Leave,
+        "back" => Command::Back,
+        "goto" => Command::Goto(arguments.first().ok_or_else(|| usage_error(&name))?.to_string()),
+        "list" => Command::List(if rest.is_empty() { None } else { Some(rest.to_string()) }),
+        "find" => match arguments.first().map(|search| search.to_lowercase()).as_deref() {
+            Some("habitable") => Command::Find(Search::Habitable),
+            Some("life") => Command::Find(Search::Life),
+            _ => return Err(usage_error(&name)),
+        },
+        "compare" => Command::Compare(parse_number(&name, &arguments, 0)?, parse_number(&name, &arguments, 1)?),
+        "advance" => Command::Advance(parse_number(&name, &arguments, 0)?),
+        "tree" => Command::Tree,
+        "clade" => Command::Clade(parse_number(&name, &arguments, 0)?),
+        "newick" => Command::Newick,
+        "web" => Command::Web,
+        "remove" => Command::Remove(parse_number(&name, &arguments, 0)?),
+        "draw" => Command::Draw,
+        "map" => Command::Map,
+        "inspect" => Command::Inspect,
+        "scan" => Command::Scan,
+        "timeline" => Command::Timeline,
+        "note" if !rest.is_empty() => Command::Note(rest.to_string()),
+        "note" => return Err(usage_error(&name)),
+        "journal" => Command::Journal,
+        "save" => Command::Save,
+        "load" => Command::Load,
+        "history" => Command::History,
+        "help" => Command::Help(arguments.first().map(|command| command.to_lowercase())),
+        "quit" | "exit" => Command::Exit,
+        "" => return Err("Type a command, or HELP to see them all.".to_string()),
+        _ => return Err(format!("There is no {} command. Type HELP to see them all.", name.to_uppercase())),
+    };
+    Ok(command)
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, arguments: &[&str], position: usize) -> Result<T, String> {
+    arguments.get(position)
+        .and_then(|argument| argument.parse().ok())
+        .ok_or_else(|| usage_error(name))
+}
+
+fn usage_error(name: &str) -> String {
+    let usage = COMMANDS.iter().find(|command| command.0 == name).map_or("", |command| command.1);
+    format!("Usage: {}", usage)
+}
+
+pub fn get_help(command: Option<&str>) -> String {
+    match command {
+        None => COMMANDS.iter()
+            .map(|command| format!("{:<30}{}", command.1, command.2))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        Some(name) => match COMMANDS.iter().find(|command| command.0 == name) {
+            Some(command) => format!("{}\n{}", command.1, command.2),
+            None => format!("There is no {} command. Type HELP to see them all.", name.to_uppercase()),
+        },
+    }
+}
+
+// Tab completion for command names, their fixed arguments, and the levels of a path
+pub struct CommandHelper;
+
+impl Completer for CommandHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |space| space + 1);
+        let word = &line[start..];
+        let command = line.split_whitespace().next().unwrap_or("").to_lowercase();
+
+        let (start, word, options): (usize, &str, Vec<&str>) = if start == 0 {
+            (start, word, COMMANDS.iter().map(|command| command.0).collect())
+        } else {
+            match command.as_str() {
+                "help" => (start, word, COMMANDS.iter().map(|command| command.0).collect()),
+                "find" => (start, word, SEARCHES.to_vec()),
+                // Levels and indices alternate, so only every other segment is a level
+                "goto" => {
+                    let segment = word.rfind('/').map_or(0, |slash| slash + 1);
+                    let depth = word[..segment].split('/').filter(|part| !part.is_empty()).count();
+                    let options = if depth % 2 == 0 { PATH_LEVELS.to_vec() } else { Vec::new() };
+                    (start + segment, &word[segment..], options)
+                }
+                _ => (start, word, Vec::new()),
+            }
+        };
+
+        let candidates = options.into_iter()
+            .filter(|option| option.starts_with(&word.to_lowercase()))
+            .map(|option| Pair { display: option.to_string(), replacement: option.to_string() })
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for CommandHelper {
+    type Hint = String;
+}
+
+impl Highlighter for CommandHelper {}
+
+impl Validator for CommandHelper {}
+
+impl Helper for CommandHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_are_case_insensitive_and_numbers_mean_info() {
+        assert!(matches!(parse("EXPLORE 3"), Ok(Command::Explore(3))));
+        assert!(matches!(parse("  compare 1   2 "), Ok(Command::Compare(1, 2))));
+        assert!(matches!(parse("Find LIFE"), Ok(Command::Find(Search::Life))));
+        assert!(matches!(parse("7"), Ok(Command::Info(7))));
+        assert!(matches!(parse("quit"), Ok(Command::Exit)));
+    }
+
+    #[test]
+    fn free_text_keeps_its_capitalisation() {
+        assert!(matches!(parse("NOTE Ceobu has   Moons").as_ref(), Ok(Command::Note(text)) if text == "Ceobu has   Moons"));
+        assert!(matches!(parse("list Red Dwarf").as_ref(), Ok(Command::List(Some(filter))) if filter == "Red Dwarf"));
+        assert!(matches!(parse("list"), Ok(Command::List(None))));
+        assert!(matches!(parse("goto /star/90/planet/1").as_ref(), Ok(Command::Goto(path)) if path == "/star/90/planet/1"));
+    }
+
+    #[test]
+    fn bad_arguments_show_the_usage() {
+        assert_eq!(parse("explore").err().as_deref(), Some("Usage: EXPLORE <n>"));
+        assert_eq!(parse("advance -5").err().as_deref(), Some("Usage: ADVANCE <millions of years>"));
+        assert_eq!(parse("compare 1").err().as_deref(), Some("Usage: COMPARE <n> <m>"));
+        assert_eq!(parse("find aliens").err().as_deref(), Some("Usage: FIND <habitable|life>"));
+        assert_eq!(parse("note").err().as_deref(), Some("Usage: NOTE <text>"));
+    }
+
+    #[test]
+    fn unknown_and_empty_commands_are_refused() {
+        assert_eq!(parse("fly 3").err().as_deref(), Some("There is no FLY command. Type HELP to see them all."));
+        assert_eq!(parse("   ").err().as_deref(), Some("Type a command, or HELP to see them all."));
+    }
+}
@@ -0,0 +1,220 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use serde_json::{json, Map};
+use strum_macros::{Display, EnumString};
+
+use crate::biosphere_metrics_generator;
+use crate::catastrophe_generator;
+use crate::galaxy_generator::{Galaxy, GalaxySettings};
+use crate::game_manager::{Level, Property, Scene};
+use crate::moon_generator;
+use crate::planetary_environment_generator::PlanetaryEnvironment;
+use crate::planetary_system_generator::PlanetarySystem;
+use crate::presentation_manager::value_to_json;
+use crate::region_generator;
+
+const PROGRESS_INTERVAL: u64 = 10_000; // stars between progress reports
+
+#[derive(Display, EnumString, Clone, Copy, PartialEq)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum ExportFormat {
+    Jsonl, // one object per line
+    Csv, // one row per line, under a header
+    Both,
+}
+
+// One table of the export. Rows are written as they are generated, so only the star being
+// written is ever held in memory.
+struct Table {
+    jsonl: Option<BufWriter<File>>,
+    csv: Option<BufWriter<File>>,
+    columns: Vec<String>, // fixed by the first row, so every line lines up with the header
+    rows: u64,
+}
+
+impl Table {
+    fn create(directory: &Path, name: &str, format: ExportFormat) -> io::Result<Table> {
+        let open = |extension: &str| -> io::Result<BufWriter<File>> {
+            Ok(BufWriter::new(File::create(directory.join(format!("{}.{}", name, extension)))?))
+        };
+        let jsonl = if format == ExportFormat::Csv { None } else { Some(open("jsonl")?) };
+        let csv = if format == ExportFormat::Jsonl { None } else { Some(open("csv")?) };
+        Ok(Table { jsonl, csv, columns: Vec::new(), rows: 0 })
+    }
+
+    // Every row starts with its own id and the ids of whatever it belongs to, then its name, then
+    // the same properties the game shows
+    fn write(&mut self, ids: &[(&str, String)], name: &str, properties: &[Property]) -> io::Result<()> {
+        let mut fields: Vec<(String, serde_json::Value)> = ids.iter()
+            .map(|(column, id)| (column.to_string(), json!(id)))
+            .collect();
+        fields.push(("name".to_string(), json!(name)));
+        fields.extend(properties.iter()
+            .map(|property| (property.get_name().clone(), value_to_json(property.get_value()))));
+
+        if let Some(jsonl) = self.jsonl.as_mut() {
+            let object: Map<String, serde_json::Value> = fields.iter().cloned().collect();
+            writeln!(jsonl, "{}", serde_json::Value::Object(object))?;
+        }
+        if let Some(csv) = self.csv.as_mut() {
+            if self.rows == 0 {
+                self.columns = fields.iter().map(|(column, _)| column.clone()).collect();
+                let header: Vec<String> = self.columns.iter().map(|column| escape(column)).collect();
+                writeln!(csv, "{}", header.join(","))?;
+            }
+            let line: Vec<String> = self.columns.iter()
+                .map(|column| fields.iter()
+                    .find(|(name, _)| name == column)
+                    .map_or(String::new(), |(_, value)| escape(&to_cell(value))))
+                .collect();
+            writeln!(csv, "{}", line.join(","))?;
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if let Some(jsonl) = self.jsonl.as_mut() {
+            jsonl.flush()?;
+        }
+        if let Some(csv) = self.csv.as_mut() {
+            csv.flush()?;
+        }
+        Ok(())
+    }
+}
+
+// Text goes into a cell as it is; numbers keep their full precision, unlike in the game's tables
+fn to_cell(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+// Cells holding a separator, a quote or a line break are quoted, with quotes doubled
+fn escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+// Writes every star of the galaxy, as it was when the game began, into stars, planets, moons,
+// species, links, biospheres and catastrophes tables. Ids are the paths the game uses, so /star/3/planet/1 is the
+// planet GOTO finds. A species living in several regions is written once, under the first of them.
+pub fn run(galaxy: &GalaxySettings, directory: &Path, format: ExportFormat,
+           panspermia: bool) -> Result<String, Box<dyn Error>> {
+    fs::create_dir_all(directory)?;
+    let mut stars = Table::create(directory, "stars", format)?;
+    let mut planets = Table::create(directory, "planets", format)?;
+    let mut moons = Table::create(directory, "moons", format)?;
+    let mut species = Table::create(directory, "species", format)?;
+    let mut biospheres = Table::create(directory, "biospheres", format)?;
+    let mut catastrophes = Table::create(directory, "catastrophes", format)?;
+    let mut links = Table::create(directory, "links", format)?;
+
+    for index in 0..galaxy.get_size() {
+        let star = galaxy.create_star(index);
+        let star_path = [Level::Star(index as usize)];
+        let star_id = Level::format_path(&star_path);
+        let unit = Galaxy::describe_star(index as usize, &star);
+        stars.write(&[("id", star_id.clone())], unit.get_name(), unit.get_properties())?;
+
+        // Life moving between stars needs every other star, so by default each system stands alone
+        let system_planets = if panspermia {
+            PlanetarySystem::new(&star, galaxy, 0).get_planets().clone()
+        } else {
+            PlanetarySystem::generate_native_planets(&star)
+        };
+        for (planet_index, planet) in system_planets.iter().enumerate() {
+            let planet_path = [star_path[0].clone(), Level::Planet(planet_index)];
+            let planet_id = Level::format_path(&planet_path);
+            let unit = PlanetarySystem::describe_planet(planet_index, planet);
+            planets.write(&[("id", planet_id.clone()), ("star_id", star_id.clone())],
+                          unit.get_name(), unit.get_properties())?;
+
+            let environment = PlanetaryEnvironment::new(planet);
+            if environment.get_metrics().get_species_richness() > 0 {
+                // Every row carries the seeding columns, blank for native life, so the CSV lines up
+                let seeding = environment.get_seeding();
+                let mut properties = biosphere_metrics_generator::describe_metrics(environment.get_metrics());
+                properties.push(Property::flag("Native", seeding.is_none()));
+                properties.push(Property::text("Seeded from", seeding.map_or(String::new(), |seeding| seeding.get_source().clone())));
+                properties.push(Property::text("Seeded by", seeding.map_or(String::new(), |seeding| seeding.get_route().to_string())));
+                biospheres.write(&[("id", planet_id.clone()), ("star_id", star_id.clone())], unit.get_name(),
+                                 &properties)?;
+            }
+            for catastrophe in environment.get_catastrophes() {
+                catastrophes.write(&[("planet_id", planet_id.clone()), ("star_id", star_id.clone())],
+                                   &catastrophe.get_kind().to_string(),
+                                   &catastrophe_generator::describe_catastrophe(catastrophe, planet.get_age()))?;
+            }
+            for (moon_index, moon) in environment.get_moons().iter().enumerate() {
+                let moon_id = Level::format_path(&[planet_path[0].clone(), planet_path[1].clone(),
+                                                   Level::Moon(moon_index)]);
+                moons.write(&[("id", moon_id), ("planet_id", planet_id.clone()), ("star_id", star_id.clone())],
+                            moon.get_designation(), &moon_generator::describe_moon(moon))?;
+            }
+            // Every species lives in at least one region
+            let species_ids: Vec<String> = (0..environment.get_species().len())
+                .map(|organism_index| {
+                    let (region_index, inhabitant_index) = environment.get_regions().iter().enumerate()
+                        .find_map(|(region_index, region)| region.get_inhabitants().iter()
+                            .position(|&inhabitant| inhabitant == organism_index)
+                            .map(|inhabitant_index| (region_index, inhabitant_index)))
+                        .unwrap();
+                    Level::format_path(&[planet_path[0].clone(), planet_path[1].clone(),
+                                         Level::Region(region_index), Level::Organism(inhabitant_index)])
+                })
+                .collect();
+            let food_web = environment.get_food_web().unwrap();
+            let phylogeny = environment.get_phylogeny().unwrap();
+            for (((organism, organism_id), web_species), lineage) in environment.get_species().into_iter()
+                .zip(&species_ids).zip(food_web.get_species()).zip(phylogeny.get_living()) {
+                // The ancestor may long be extinct, so it goes by name rather than by id
+                let lineage = phylogeny.get_lineage(lineage).unwrap();
+                let ancestor = lineage.get_parent()
+                    .map_or(String::new(), |parent| phylogeny.get_lineage(parent).unwrap().get_organism().get_name().clone());
+                let mut properties = region_generator::describe_organism(organism);
+                properties.push(Property::number("Population", web_species.get_population()));
+                properties.push(Property::text("Ancestor", ancestor));
+                properties.push(Property::integer("Appeared (Myr ago)", phylogeny.get_present() - lineage.get_origin()));
+                species.write(&[("id", organism_id.clone()), ("planet_id", planet_id.clone()),
+                                ("star_id", star_id.clone())], organism.get_name(), &properties)?;
+            }
+            for link in food_web.get_links() {
+                let (prey, predator) = (&food_web.get_species()[link.get_prey()],
+                                        &food_web.get_species()[link.get_predator()]);
+                links.write(&[("prey_id", species_ids[link.get_prey()].clone()),
+                              ("predator_id", species_ids[link.get_predator()].clone()),
+                              ("planet_id", planet_id.clone()), ("star_id", star_id.clone())],
+                            &format!("{} eats {}", predator.get_name(), prey.get_name()), &[
+                                Property::text("Prey role", prey.get_role()),
+                                Property::text("Predator role", predator.get_role()),
+                            ])?;
+            }
+        }
+
+        if (index + 1) % PROGRESS_INTERVAL == 0 {
+            eprintln!("Exported {} of {} stars.", index + 1, galaxy.get_size());
+        }
+    }
+
+    for table in [&mut stars, &mut planets, &mut moons, &mut species, &mut biospheres,
+                  &mut catastrophes, &mut links] {
+        table.finish()?;
+    }
+    let mut summary = format!("Wrote {} stars, {} planets, {} moons, {} species, {} food web links, {} \
+                               biospheres and {} catastrophes to {}.", stars.rows, planets.rows, moons.rows,
+                              species.rows, links.rows, biospheres.rows, catastrophes.rows, directory.display());
+    if !panspermia {
+        summary.push_str(" Life did not travel between stars, so planets it would have seeded may differ \
+                          from the game's; pass --panspermia to match it.");
+    }
+    Ok(summary)
+}
@@ -0,0 +1,342 @@
+use rand::prelude::*;
+use strum_macros::Display;
+
+use crate::organism_generator::{Habitat, Organism, TrophicRole};
+
+const PRODUCER_GROWTH_RATE: f64 = 1.0; // per unit of simulated time
+const PRODUCER_CARRYING_CAPACITY: f64 = 1000.0;
+const CONSUMER_MORTALITY: f64 = 0.1;
+const CONSUMER_SELF_LIMITATION: f64 = 0.001; // competition for space and mates among consumers
+const ATTACK_RATE: f64 = 0.01;
+const CONVERSION_EFFICIENCY: f64 = 0.1; // roughly a tenth of the energy eaten moves up a level
+const PARASITE_ATTACK_RATE: f64 = 0.005;
+const PARASITE_CONVERSION_EFFICIENCY: f64 = 0.3;
+const DECOMPOSER_ATTACK_RATE: f64 = 0.002;
+const DECOMPOSER_CONVERSION_EFFICIENCY: f64 = 0.2;
+const MAXIMUM_PARASITE_HOSTS: usize = 2;
+const ATTACK_RATE_VARIATION: f64 = 0.5; // some consumers are better at catching some prey
+const INITIAL_POPULATION: f64 = 10.0;
+const EXTINCTION_THRESHOLD: f64 = 0.01;
+const TIME_STEP: f64 = 0.01;
+const SETTLING_TIME: f64 = 200.0; // lets populations settle before the player sees them
+const SETTLING_TOLERANCE: f64 = 1e-9; // relative change per step below which a web has settled
+const STABILITY_WINDOW: f64 = 50.0;
+const STABILITY_TOLERANCE: f64 = 0.01; // largest relative swing a stable population shows
+const PERTURBATION_DURATION: f64 = 100.0;
+const PERTURBATION_SNAPSHOTS: usize = 10;
+
+#[derive(Display, Clone, PartialEq)]
+pub enum Stability {
+    Stable,
+    Oscillating,
+    Collapsed,
+}
+
+#[derive(Clone)]
+pub struct Species {
+    name: String,
+    role: TrophicRole,
+    population: f64,
+    growth_rate: f64,
+    self_limitation: f64,
+}
+
+impl Species {
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn get_role(&self) -> &TrophicRole {
+        &self.role
+    }
+
+    pub fn get_population(&self) -> f64 {
+        self.population
+    }
+
+    pub fn is_extant(&self) -> bool {
+        self.population > 0.0
+    }
+}
+
+// Energy flows from prey to predator
+#[derive(Clone)]
+pub struct Link {
+    prey: usize,
+    predator: usize,
+    attack_rate: f64,
+    efficiency: f64,
+}
+
+impl Link {
+    pub fn get_prey(&self) -> usize {
+        self.prey
+    }
+
+    pub fn get_predator(&self) -> usize {
+        self.predator
+    }
+}
+
+// Who eats whom in a biosphere, with population sizes following generalized Lotka–Volterra
+// dynamics: producers grow logistically, and consumers grow by what they eat and die off without it.
+#[derive(Clone)]
+pub struct FoodWeb {
+    species: Vec<Species>,
+    links: Vec<Link>,
+}
+
+impl FoodWeb {
+    pub fn new(organisms: &[&Organism], seed: u64) -> FoodWeb {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let species = organisms.iter().map(|organism| {
+            let producer = *organism.get_trophic_role() == TrophicRole::Producer;
+            Species {
+                name: organism.get_name().clone(),
+                role: organism.get_trophic_role().clone(),
+                population: INITIAL_POPULATION,
+                growth_rate: if producer { PRODUCER_GROWTH_RATE } else { -CONSUMER_MORTALITY },
+                self_limitation: if producer {
+                    PRODUCER_GROWTH_RATE / PRODUCER_CARRYING_CAPACITY
+                } else {
+                    CONSUMER_SELF_LIMITATION
+                },
+            }
+        }).collect();
+
+        let mut food_web = FoodWeb { species, links: Vec::new() };
+        food_web.links = Self::generate_links(organisms, &mut rng);
+        food_web.settle();
+        food_web
+    }
+
+    fn generate_links(organisms: &[&Organism], rng: &mut impl Rng) -> Vec<Link> {
+        let mut links = Vec::new();
+
+        for (predator, consumer) in organisms.iter().enumerate() {
+            let mut prey: Vec<usize> = (0..organisms.len())
+                .filter(|&prey| prey != predator
+                    && Self::habitats_overlap(consumer.get_habitat(), organisms[prey].get_habitat())
+                    && Self::is_food(consumer.get_trophic_role(), organisms[prey].get_trophic_role()))
+                .collect();
+
+            let (attack_rate, efficiency) = match consumer.get_trophic_role() {
+                TrophicRole::Producer => continue,
+                TrophicRole::Parasite => {
+                    prey.shuffle(rng);
+                    prey.truncate(MAXIMUM_PARASITE_HOSTS);
+                    (PARASITE_ATTACK_RATE, PARASITE_CONVERSION_EFFICIENCY)
+                }
+                TrophicRole::Decomposer => (DECOMPOSER_ATTACK_RATE, DECOMPOSER_CONVERSION_EFFICIENCY),
+                _ => (ATTACK_RATE, CONVERSION_EFFICIENCY),
+            };
+
+            for prey in prey {
+                let attack_rate = attack_rate
+                    * rng.gen_range(1.0 - ATTACK_RATE_VARIATION..=1.0 + ATTACK_RATE_VARIATION);
+                links.push(Link { prey, predator, attack_rate, efficiency });
+            }
+        }
+        links
+    }
+
+    fn is_food(consumer: &TrophicRole, food: &TrophicRole) -> bool {
+        match consumer {
+            TrophicRole::Producer => false,
+            TrophicRole::Grazer => *food == TrophicRole::Producer,
+            TrophicRole::Predator => *food == TrophicRole::Grazer || *food == TrophicRole::Predator,
+            // Decomposers break down the remains of everything that isn't itself a decomposer
+            TrophicRole::Decomposer => *food != TrophicRole::Decomposer,
+            TrophicRole::Parasite => *food != TrophicRole::Parasite,
+        }
+    }
+
+    fn habitats_overlap(a: &Habitat, b: &Habitat) -> bool {
+        a == b || matches!(
+            (a, b),
+            (Habitat::Pelagic, Habitat::Benthic) | (Habitat::Benthic, Habitat::Pelagic)
+            | (Habitat::Benthic, Habitat::Subsurface) | (Habitat::Subsurface, Habitat::Benthic)
+            | (Habitat::Terrestrial, Habitat::Subsurface) | (Habitat::Subsurface, Habitat::Terrestrial)
+            | (Habitat::Terrestrial, Habitat::Aerial) | (Habitat::Aerial, Habitat::Terrestrial)
+        )
+    }
+
+    // Euler integration; populations that dwindle below the threshold die out for good
+    pub fn simulate(&mut self, duration: f64) {
+        let steps = (duration / TIME_STEP).round() as usize;
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    // Simulates for up to the settling time, stopping early once populations stop changing
+    fn settle(&mut self) {
+        let steps = (SETTLING_TIME / TIME_STEP).round() as usize;
+        for _ in 0..steps {
+            if self.step() < SETTLING_TOLERANCE {
+                break;
+            }
+        }
+    }
+
+    // Advances one time step and returns the largest relative change in any population
+    fn step(&mut self) -> f64 {
+        let mut change: Vec<f64> = self.species.iter()
+            .map(|species| species.population
+                * (species.growth_rate - species.self_limitation * species.population))
+            .collect();
+
+        for link in &self.links {
+            let eaten = link.attack_rate
+                * self.species[link.prey].population * self.species[link.predator].population;
+            change[link.prey] -= eaten;
+            change[link.predator] += link.efficiency * eaten;
+        }
+
+        let mut largest_change: f64 = 0.0;
+        for (species, change) in self.species.iter_mut().zip(change) {
+            if species.population > 0.0 {
+                let before = species.population;
+                species.population += change * TIME_STEP;
+                if species.population < EXTINCTION_THRESHOLD {
+                    species.population = 0.0;
+                }
+                largest_change = largest_change.max((species.population - before).abs() / before);
+            }
+        }
+        largest_change
+    }
+
+    // Runs a copy of the web forward and measures how much its populations swing
+    pub fn get_stability(&self) -> Stability {
+        if self.species.iter().all(|species| !species.is_extant()) {
+            return Stability::Collapsed;
+        }
+
+        let mut future = self.clone();
+        let mut minimum: Vec<f64> = self.get_populations();
+        let mut maximum = minimum.clone();
+        for _ in 0..PERTURBATION_SNAPSHOTS {
+            future.simulate(STABILITY_WINDOW / PERTURBATION_SNAPSHOTS as f64);
+            for (i, population) in future.get_populations().into_iter().enumerate() {
+                minimum[i] = minimum[i].min(population);
+                maximum[i] = maximum[i].max(population);
+            }
+        }
+
+        let lost_species = (0..self.species.len())
+            .any(|i| self.species[i].is_extant() && !future.species[i].is_extant());
+        let largest_swing = (0..self.species.len())
+            .filter(|&i| maximum[i] > 0.0)
+            .map(|i| (maximum[i] - minimum[i]) / maximum[i])
+            .fold(0.0, f64::max);
+
+        if lost_species {
+            Stability::Collapsed
+        } else if largest_swing <= STABILITY_TOLERANCE {
+            Stability::Stable
+        } else {
+            Stability::Oscillating
+        }
+    }
+
+    // Removes a species and follows the web as it responds, reporting populations as they change
+    // and any species lost along with it
+    pub fn remove_species(&mut self, index: usize) -> String {
+        if index >= self.species.len() || !self.species[index].is_extant() {
+            return "Invalid coordinates.".to_string();
+        }
+
+        let before = self.get_populations();
+        self.species[index].population = 0.0;
+
+        let mut s = format!("{} has been removed from the food web.\n\ntime", self.species[index].name);
+        for species in &self.species {
+            s.push_str(&format!("\t{}", species.name));
+        }
+        s.push('\n');
+        for snapshot in 1..=PERTURBATION_SNAPSHOTS {
+            self.simulate(PERTURBATION_DURATION / PERTURBATION_SNAPSHOTS as f64);
+            s.push_str(&format!("{}", snapshot as f64 * PERTURBATION_DURATION / PERTURBATION_SNAPSHOTS as f64));
+            for species in &self.species {
+                s.push_str(&format!("\t{:.1}", species.population));
+            }
+            s.push('\n');
+        }
+
+        let lost: Vec<&str> = (0..self.species.len())
+            .filter(|&i| i != index && before[i] > 0.0 && !self.species[i].is_extant())
+            .map(|i| self.species[i].name.as_str())
+            .collect();
+        if lost.is_empty() {
+            s.push_str("\nNo other species were lost.");
+        } else {
+            s.push_str(&format!("\nThe loss cascaded through the web, taking {} with it.", lost.join(", ")));
+        }
+        s
+    }
+
+    pub fn get_species(&self) -> &Vec<Species> {
+        &self.species
+    }
+
+    pub fn get_links(&self) -> &Vec<Link> {
+        &self.links
+    }
+
+    pub fn get_populations(&self) -> Vec<f64> {
+        self.species.iter().map(|species| species.population).collect()
+    }
+
+    pub fn get_prey(&self, index: usize) -> Vec<usize> {
+        self.links.iter().filter(|link| link.predator == index).map(|link| link.prey).collect()
+    }
+
+    pub fn get_predators(&self, index: usize) -> Vec<usize> {
+        self.links.iter().filter(|link| link.prey == index).map(|link| link.predator).collect()
+    }
+
+    // Producers sit at level 1; consumers one level above the average of what they eat
+    pub fn get_trophic_levels(&self) -> Vec<f64> {
+        let mut levels = vec![1.0; self.species.len()];
+        for _ in 0..self.species.len() {
+            for i in 0..self.species.len() {
+                let prey = self.get_prey(i);
+                if !prey.is_empty() {
+                    levels[i] = 1.0 + prey.iter().map(|&j| levels[j]).sum::<f64>() / prey.len() as f64;
+                }
+            }
+        }
+        levels
+    }
+
+    pub fn get_species_info(&self, index: usize) -> String {
+        let species = &self.species[index];
+        if !species.is_extant() {
+            return format!("{} has died out.", species.name);
+        }
+        let names = |indices: Vec<usize>| -> String {
+            let names: Vec<&str> = indices.iter().map(|&i| self.species[i].name.as_str()).collect();
+            if names.is_empty() { "nothing".to_string() } else { names.join(", ") }
+        };
+        format!("Its population is {:.1}; it feeds on {} and is eaten by {}.",
+                species.population, names(self.get_prey(index)), names(self.get_predators(index)))
+    }
+
+    pub fn get_info(&self) -> String {
+        let levels = self.get_trophic_levels();
+        let mut s = String::new();
+        for (i, (species, level)) in self.species.iter().zip(levels).enumerate() {
+            let prey: Vec<String> = self.get_prey(i).iter().map(|prey| prey.to_string()).collect();
+            s.push_str(&format!("{}: {} ({}, trophic level {:.1}), population {:.1}",
+                                i, species.name, species.role, level, species.population));
+            if !prey.is_empty() {
+                s.push_str(&format!(", eats {}", prey.join(" ")));
+            }
+            s.push('\n');
+        }
+        s.push_str(&format!("The food web is {}.", self.get_stability()));
+        s
+    }
+}
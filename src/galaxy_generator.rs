@@ -1,20 +1,109 @@
-use std::fmt::format;
-use crate::game_manager::{Scene, SceneName};
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+use crate::game_manager::{Level, Property, Scene, SceneName, Unit};
+use crate::panspermia_generator::Neighbourhood;
+use crate::planetary_system_generator::PlanetarySystem;
 use crate::star_generator;
 
+const GALAXY_SEED_STRIDE: u64 = 1_000_000_007; // keeps the stars of different galaxies from sharing seeds
+
+#[derive(Display, EnumString, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+#[serde(rename_all = "lowercase")]
+pub enum GalaxyShape {
+    #[default]
+    Disc,
+    Spiral,
+    Elliptical,
+    Ring,
+}
+
+// Everything that decides which galaxy is generated; the same settings always give the same stars
+#[derive(Clone, Default)]
+pub struct GalaxySettings {
+    seed: u64,
+    size: u64, // in stars
+    shape: GalaxyShape,
+    neighbourhood: Rc<Neighbourhood>, // shared by every copy, so it is only worked out once
+}
+
+impl GalaxySettings {
+    pub fn new(seed: u64, size: u64, shape: GalaxyShape) -> GalaxySettings {
+        GalaxySettings { seed, size, shape, neighbourhood: Rc::new(Neighbourhood::default()) }
+    }
+
+    // Galaxy 0 keeps the seeds stars had before galaxies could be chosen, so older saves still
+    // find the same stars
+    pub fn get_star_seed(&self, index: u64) -> u64 {
+        index.wrapping_add(self.seed.wrapping_mul(GALAXY_SEED_STRIDE))
+    }
+
+    // The star at the given index, as it was when the game began
+    pub fn create_star(&self, index: u64) -> star_generator::Star {
+        star_generator::Star::new(self.get_star_seed(index), &self.shape)
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn get_shape(&self) -> &GalaxyShape {
+        &self.shape
+    }
+
+    pub fn get_neighbourhood(&self) -> &Neighbourhood {
+        &self.neighbourhood
+    }
+}
+
 pub struct Galaxy {
+    settings: GalaxySettings,
     stars: Vec<star_generator::Star>,
+    elapsed_time: u16, // in millions of years since the game began
 }
 
 impl Galaxy {
-    pub fn new(number_of_stars: u64) -> Galaxy {
+    pub fn new(settings: &GalaxySettings, elapsed_time: u16) -> Galaxy {
         let mut stars = Vec::new();
 
-        for i in 0..number_of_stars {
-            stars.push(star_generator::Star::new(i as u64));
+        for i in 0..settings.get_size() {
+            let mut star = settings.create_star(i);
+            star.advance(elapsed_time);
+            stars.push(star);
         }
 
-        Galaxy { stars }
+        Galaxy { settings: settings.clone(), stars, elapsed_time }
+    }
+
+    // A star as a unit of the galaxy, at the given index
+    pub fn describe_star(index: usize, star: &star_generator::Star) -> Unit {
+        let habitable_zone = star.get_habitable_zone_at(star.get_age());
+        let position = star.get_position();
+        let properties = vec![
+            Property::text("Designation", star.get_designation()),
+            Property::text("Proper name", star.get_proper_name().map_or("", |name| name.as_str())),
+            Property::integer("Seed", star.get_seed() as i64),
+            Property::text("Class", star.get_class()),
+            Property::text("Color", star.get_color()),
+            Property::integer("Age (Myr)", star.get_age()),
+            Property::number("Mass (M☉)", star.get_mass()),
+            Property::integer("Temperature (K)", star.get_temperature()),
+            Property::number("Radius (R☉)", star.get_radius()),
+            Property::number("Luminosity (L☉)", star.get_luminosity()),
+            Property::number("Habitable zone inner edge (AU)", habitable_zone.0),
+            Property::number("Habitable zone outer edge (AU)", habitable_zone.1),
+            Property::number("Distance from centre (ly)", (position.0.powi(2) + position.1.powi(2)).sqrt()),
+            Property::number("X (ly)", position.0),
+            Property::number("Y (ly)", position.1),
+        ];
+        Unit::new(Level::Star(index), star.get_class(), star.get_name(), properties, star.get_info())
     }
 }
 
@@ -22,24 +111,44 @@ impl Scene for Galaxy {
     fn get_scene_name(&self) -> SceneName {
         SceneName::Galaxy
     }
-    
-    fn get_system_info(&self) -> String {
-        let mut s = String::new();
-        for i in 0..self.stars.len() {
-            s.push_str(&self.stars[i].get_class().to_string());
-            s.push_str("_");
-            s.push_str(&i.to_string());
-            s.push_str(" ");
-        }
-        format!("{}", s)
+
+    fn get_name(&self) -> String {
+        "Galaxy".to_string()
     }
 
-    fn get_unit_info(&self, index: usize) -> String {
-        if index < self.stars.len() {
-            self.stars[index].get_info()
-        }
-        else {
-            format!("Invalid coordinates.")
+    fn get_description(&self) -> String {
+        format!("The {} galaxy holds {} stars.", self.settings.get_shape(), self.stars.len())
+    }
+
+    fn get_properties(&self) -> Vec<Property> {
+        vec![
+            Property::integer("Seed", self.settings.get_seed() as i64),
+            Property::text("Shape", self.settings.get_shape()),
+            Property::integer("Stars", self.stars.len() as i64),
+        ]
+    }
+
+    fn get_units(&self) -> Vec<Unit> {
+        self.stars.iter().enumerate()
+            .map(|(index, star)| Self::describe_star(index, star))
+            .collect()
+    }
+
+    fn enter(&self, level: &Level) -> Option<Box<dyn Scene>> {
+        match *level {
+            Level::Star(index) => {
+                let star = self.stars.get(index)?;
+                Some(Box::new(PlanetarySystem::new(star, &self.settings, self.elapsed_time)))
+            }
+            _ => None,
         }
     }
+
+    fn get_child_level(&self, index: usize) -> Option<Level> {
+        Some(Level::Star(index))
+    }
+
+    fn get_stars(&self) -> Option<&Vec<star_generator::Star>> {
+        Some(&self.stars)
+    }
 }
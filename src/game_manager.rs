@@ -1,69 +1,586 @@
-use crate::planet_generator;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use strum_macros::Display;
+
+use crate::civilization_generator::Civilization;
+use crate::command_manager::Search;
+use crate::biosphere_metrics_generator::BiosphereMetrics;
+use crate::catastrophe_generator::Catastrophe;
+use crate::food_web_generator::FoodWeb;
+use crate::phylogeny_generator::Phylogeny;
+use crate::panspermia_generator;
+use crate::save_manager::SaveFile;
 use crate::star_generator;
+use crate::galaxy_generator::GalaxySettings;
 use crate::Galaxy;
 use crate::PlanetarySystem;
 use crate::PlanetaryEnvironment;
 
+const SCENE_CACHE_CAPACITY: usize = 64; // scenes kept before those off the current path are dropped
+const HISTORY_CAPACITY: usize = 64; // earlier paths kept for going back; the oldest are forgotten first
+
+#[derive(Display, Clone, PartialEq)]
 pub enum SceneName {
     Galaxy,
     PlanetarySystem,
     PlanetaryEnvironment,
+    Moon,
+    Region,
+    Organism,
+}
+
+// One step down from the galaxy: the kind of place entered and its index among its siblings
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum Level {
+    Star(usize),
+    Planet(usize),
+    Moon(usize),
+    Region(usize),
+    Organism(usize),
+}
+
+impl Level {
+    fn new(kind: &str, index: usize) -> Option<Level> {
+        match kind {
+            "star" => Some(Level::Star(index)),
+            "planet" => Some(Level::Planet(index)),
+            "moon" => Some(Level::Moon(index)),
+            "region" => Some(Level::Region(index)),
+            "organism" => Some(Level::Organism(index)),
+            _ => None,
+        }
+    }
+
+    // Paths are written as kind and index pairs from the galaxy inward, e.g. "/star/42/planet/3"
+    pub fn parse_path(path: &str) -> Option<Vec<Level>> {
+        let parts: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+        if !parts.len().is_multiple_of(2) {
+            return None;
+        }
+        parts.chunks(2)
+            .map(|pair| Level::new(&pair[0].to_lowercase(), pair[1].parse().ok()?))
+            .collect()
+    }
+
+    pub fn get_index(&self) -> usize {
+        match *self {
+            Level::Star(index) | Level::Planet(index) | Level::Moon(index) | Level::Region(index)
+            | Level::Organism(index) => index,
+        }
+    }
+
+    // The kind of place, as written in paths
+    pub fn get_kind(&self) -> &'static str {
+        match self {
+            Level::Star(_) => "star",
+            Level::Planet(_) => "planet",
+            Level::Moon(_) => "moon",
+            Level::Region(_) => "region",
+            Level::Organism(_) => "organism",
+        }
+    }
+
+    pub fn format_path(path: &[Level]) -> String {
+        if path.is_empty() {
+            return "/".to_string();
+        }
+        path.iter().map(|level| format!("/{}", level)).collect()
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.get_kind(), self.get_index())
+    }
+}
+
+// A typed value frontends can show, sort or export without parsing prose
+#[derive(Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Integer(i64),
+    Number(f64),
+    Flag(bool),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Text(text) => write!(f, "{}", text),
+            Value::Integer(integer) => write!(f, "{}", integer),
+            Value::Number(number) => write!(f, "{:.3}", number),
+            Value::Flag(flag) => write!(f, "{}", if *flag { "yes" } else { "no" }),
+        }
+    }
+}
+
+// A named fact about a scene or one of its units, with its units of measure in the name
+#[derive(Clone)]
+pub struct Property {
+    name: String,
+    value: Value,
+}
+
+impl Property {
+    pub fn new(name: &str, value: Value) -> Property {
+        Property { name: name.to_string(), value }
+    }
+
+    pub fn text(name: &str, text: impl ToString) -> Property {
+        Property::new(name, Value::Text(text.to_string()))
+    }
+
+    pub fn integer(name: &str, integer: impl Into<i64>) -> Property {
+        Property::new(name, Value::Integer(integer.into()))
+    }
+
+    pub fn number(name: &str, number: f64) -> Property {
+        Property::new(name, Value::Number(number))
+    }
+
+    pub fn flag(name: &str, flag: bool) -> Property {
+        Property::new(name, Value::Flag(flag))
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn get_value(&self) -> &Value {
+        &self.value
+    }
+}
+
+// One place or organism within a scene. Its path starts out relative to the scene, and can be
+// anchored to the galaxy once the scene's own path is known.
+#[derive(Clone)]
+pub struct Unit {
+    index: usize, // the bare index that picks it out within its scene
+    path: Vec<Level>,
+    kind: String, // a short category such as a star's class or a region's biome
+    name: String,
+    properties: Vec<Property>,
+    description: String, // the same facts as prose
+}
+
+impl Unit {
+    pub fn new(level: Level, kind: impl ToString, name: impl ToString, properties: Vec<Property>,
+               description: String) -> Unit {
+        Unit { index: level.get_index(), path: vec![level], kind: kind.to_string(), name: name.to_string(),
+               properties, description }
+    }
+
+    // For scenes holding more than one kind of place, where a unit's index among its own kind
+    // isn't its place in the scene
+    pub fn at(mut self, index: usize) -> Unit {
+        self.index = index;
+        self
+    }
+
+    pub fn within(mut self, parent: &[Level]) -> Unit {
+        self.path.splice(0..0, parent.iter().cloned());
+        self
+    }
+
+    // Stable across sessions, since every place is generated from its path
+    pub fn get_id(&self) -> String {
+        Level::format_path(&self.path)
+    }
+
+    pub fn get_path(&self) -> &Vec<Level> {
+        &self.path
+    }
+
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    pub fn get_kind(&self) -> &String {
+        &self.kind
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn get_properties(&self) -> &Vec<Property> {
+        &self.properties
+    }
+
+    pub fn get_property(&self, name: &str) -> Option<&Value> {
+        self.properties.iter().find(|property| property.name == name).map(|property| &property.value)
+    }
+
+    pub fn get_description(&self) -> &String {
+        &self.description
+    }
 }
 
 pub trait Scene {
     fn get_scene_name(&self) -> SceneName;
-    fn get_system_info(&self) -> String;
-    fn get_unit_info(&self, index: usize) -> String;
+    fn get_name(&self) -> String;
+
+    // The scene as prose; anything a frontend needs to compute with is in the properties
+    fn get_description(&self) -> String;
+
+    fn get_properties(&self) -> Vec<Property> {
+        Vec::new()
+    }
+
+    // The places or organisms a bare index picks out here, in index order
+    fn get_units(&self) -> Vec<Unit> {
+        Vec::new()
+    }
+
+    // Builds the scene one level further in, if this scene has such a place
+    fn enter(&self, _level: &Level) -> Option<Box<dyn Scene>> {
+        None
+    }
+
+    // The kind of place a bare index leads to from here, if anywhere
+    fn get_child_level(&self, _index: usize) -> Option<Level> {
+        None
+    }
+
+    // What a visitor learns just by arriving
+    fn get_discoveries(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    // Only scenes with a biosphere have a tree of life or a food web
+    fn get_phylogeny(&self) -> Option<&Phylogeny> {
+        None
+    }
+
+    fn get_food_web(&self) -> Option<&FoodWeb> {
+        None
+    }
+
+    fn get_food_web_mut(&mut self) -> Option<&mut FoodWeb> {
+        None
+    }
+
+    fn get_metrics(&self) -> Option<&BiosphereMetrics> {
+        None
+    }
+
+    // The catastrophes that have struck life here, for scenes that have a history
+    fn get_catastrophes(&self) -> Option<&Vec<Catastrophe>> {
+        None
+    }
+
+    // Only the galaxy has stars to chart, and only a planetary system has orbits
+    fn get_stars(&self) -> Option<&Vec<star_generator::Star>> {
+        None
+    }
+
+    fn get_planetary_system(&self) -> Option<&PlanetarySystem> {
+        None
+    }
 }
 
 pub struct Game {
-    pub scene: Box <dyn Scene>,
-    galaxy_size: u64,
-    star_seed: star_generator::Star,
-    planet_seed: planet_generator::Planet,
+    galaxy: GalaxySettings,
+    elapsed_time: u16, // in millions of years since the game began
+    path: Vec<Level>, // from the galaxy to the current scene
+    scenes: HashMap<Vec<Level>, Box<dyn Scene>>, // every scene built at the current time, by path
+    history: Vec<Vec<Level>>, // earlier paths, most recent last, for going back
+    discoveries: Vec<String>, // living worlds and civilizations found so far, in order
+    notes: Vec<String>,
 }
 
 impl Game {
-    pub fn new(number_of_stars: u64) -> Game {
-
-        let star = star_generator::Star::new(0);
-        let planet = planet_generator::Planet::new(&star, 1);
+    pub fn new(galaxy: GalaxySettings) -> Game {
+        let mut scenes: HashMap<Vec<Level>, Box<dyn Scene>> = HashMap::new();
+        scenes.insert(Vec::new(), Box::new(Galaxy::new(&galaxy, 0)));
 
         Game {
-            scene: Box::new(Galaxy::new(number_of_stars)),
-            galaxy_size: number_of_stars,
-            star_seed: star,
-            planet_seed: planet,
+            galaxy,
+            elapsed_time: 0,
+            path: Vec::new(),
+            scenes,
+            history: Vec::new(),
+            discoveries: Vec::new(),
+            notes: Vec::new(),
         }
     }
 
-    pub fn step_in(&mut self, index: usize){
-        match self.scene.get_scene_name() {
-            SceneName::Galaxy => {
-                self.star_seed = star_generator::Star::new(index as u64);
-                self.scene = Box::new(PlanetarySystem::new(&self.star_seed));
-            }
-            SceneName::PlanetarySystem => {
-                self.planet_seed = planet_generator::Planet::new(&self.star_seed, index as u8 + 1);
-                self.scene = Box::new(PlanetaryEnvironment::new(&self.planet_seed));
+    // Rebuilds a saved game by retracing its path from the galaxy inward at the saved time
+    pub fn load(path: &Path) -> Result<Game, Box<dyn Error>> {
+        let save = SaveFile::read(path)?;
+        let levels = Level::parse_path(save.get_path()).ok_or("the save's path is malformed")?;
+        let mut game = Game::new(GalaxySettings::new(save.get_galaxy_seed(), save.get_galaxy_size(),
+                                                     *save.get_galaxy_shape()));
+        game.elapsed_time = save.get_elapsed_time();
+        game.reset_scenes();
+        game.path = game.reach(&levels);
+        game.discoveries = save.get_discoveries().clone();
+        game.notes = save.get_notes().clone();
+        Ok(game)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        SaveFile::new(&self.galaxy, self.elapsed_time, Level::format_path(&self.path),
+                      self.discoveries.clone(), self.notes.clone()).write(path)
+    }
+
+    pub fn get_scene(&self) -> &dyn Scene {
+        self.scenes[&self.path].as_ref()
+    }
+
+    pub fn get_scene_mut(&mut self) -> &mut dyn Scene {
+        self.scenes.get_mut(&self.path).unwrap().as_mut()
+    }
+
+    pub fn get_path(&self) -> &Vec<Level> {
+        &self.path
+    }
+
+    // The name of every scene from the galaxy down to the current one
+    pub fn get_scene_names(&self) -> Vec<String> {
+        (0..=self.path.len())
+            .map(|depth| self.scenes[&self.path[..depth]].get_name())
+            .collect()
+    }
+
+    // The name of every place from the galaxy to here, followed by the path itself
+    pub fn get_breadcrumbs(&self) -> String {
+        format!("{} [{}]", self.get_scene_names().join(" > "), Level::format_path(&self.path))
+    }
+
+    pub fn get_elapsed_time(&self) -> u16 {
+        self.elapsed_time
+    }
+
+    pub fn add_note(&mut self, note: String) {
+        self.notes.push(note);
+    }
+
+    fn discover(&mut self, discovery: String) {
+        if !self.discoveries.contains(&discovery) {
+            self.discoveries.push(discovery);
+        }
+    }
+
+    pub fn get_journal_info(&self) -> String {
+        let discoveries = if self.discoveries.is_empty() { "none yet".to_string() } else { self.discoveries.join("\n") };
+        let notes = if self.notes.is_empty() { "none yet".to_string() } else { self.notes.join("\n") };
+        format!("Discoveries:\n{}\n\nNotes:\n{}", discoveries, notes)
+    }
+
+    // Builds whatever scenes along the path aren't cached yet, stopping where the path leads
+    // nowhere, and returns how much of it could be reached
+    fn reach(&mut self, path: &[Level]) -> Vec<Level> {
+        for depth in 1..=path.len() {
+            let key = &path[..depth];
+            if self.scenes.contains_key(key) {
+                continue;
             }
-            SceneName::PlanetaryEnvironment => {
-                println!("You can't go any further inward. \n");
+            let Some(scene) = self.scenes[&path[..depth - 1]].enter(&path[depth - 1]) else {
+                return path[..depth - 1].to_vec();
+            };
+            // Once the cache is full, only the scenes leading here and to where we are now are
+            // kept, since the rest of the path may still lead nowhere
+            if self.scenes.len() >= SCENE_CACHE_CAPACITY {
+                let current = &self.path;
+                self.scenes.retain(|cached, _| path.starts_with(cached) || current.starts_with(cached));
             }
+            self.scenes.insert(key.to_vec(), scene);
+        }
+        path.to_vec()
+    }
+
+    fn reset_scenes(&mut self) {
+        self.scenes.clear();
+        self.scenes.insert(Vec::new(), Box::new(Galaxy::new(&self.galaxy, self.elapsed_time)));
+    }
+
+    // Moves to the scene at the end of the path, remembering where we were so we can go back
+    pub fn go_to(&mut self, path: &[Level]) -> Result<(), String> {
+        if self.reach(path).len() < path.len() {
+            return Err(format!("There is nothing at {}.", Level::format_path(path)));
+        }
+        self.history.push(std::mem::replace(&mut self.path, path.to_vec()));
+        if self.history.len() > HISTORY_CAPACITY {
+            self.history.remove(0);
+        }
+        for discovery in self.get_scene().get_discoveries() {
+            self.discover(discovery);
+        }
+        Ok(())
+    }
+
+    pub fn step_in(&mut self, index: usize) -> Result<(), String> {
+        let level = self.get_scene().get_child_level(index)
+            .ok_or("You can't go any further inward.")?;
+        let mut path = self.path.clone();
+        path.push(level);
+        self.go_to(&path).map_err(|_| "Invalid coordinates.".to_string())
+    }
+
+    pub fn step_out(&mut self) -> Result<(), String> {
+        if self.path.is_empty() {
+            return Err("You can't go any further outward.".to_string());
         }
+        let path = self.path[..self.path.len() - 1].to_vec();
+        self.go_to(&path)
     }
 
-    pub fn step_out(&mut self){
-        match self.scene.get_scene_name() {
-            SceneName::Galaxy => {
-                println!("You can't go any further outward. \n");
+    // Returns to the previous scene. Places that have since vanished are retraced as far as they
+    // still go.
+    pub fn back(&mut self) -> Result<(), String> {
+        let path = self.history.pop().ok_or("There is nowhere to go back to.")?;
+        self.path = self.reach(&path);
+        Ok(())
+    }
+
+    // The places or organisms in the current scene, with paths from the galaxy
+    pub fn get_units(&self) -> Vec<Unit> {
+        self.get_scene().get_units().into_iter()
+            .map(|unit| unit.within(&self.path))
+            .collect()
+    }
+
+    // Every place or organism in the current scene whose kind or name contains the filter, ignoring case
+    pub fn list(&self, filter: Option<&str>) -> Vec<Unit> {
+        let filter = filter.map(|filter| filter.to_lowercase());
+        self.get_units().into_iter()
+            .filter(|unit| filter.as_ref().is_none_or(|filter| {
+                format!("{} {}", unit.get_kind(), unit.get_name()).to_lowercase().contains(filter)
+            }))
+            .collect()
+    }
+
+    // Two places or organisms in the current scene, to be set side by side
+    pub fn compare(&self, first: usize, second: usize) -> Option<(Unit, Unit)> {
+        let units = self.get_units();
+        Some((units.get(first)?.clone(), units.get(second)?.clone()))
+    }
+
+    // Searches every star for planets that could hold life or already do
+    pub fn find(&self, search: &Search) -> Vec<Unit> {
+        let mut results = Vec::new();
+        for seed in 0..self.galaxy.get_size() {
+            let mut star = self.galaxy.create_star(seed);
+            star.advance(self.elapsed_time);
+            let planets = match search {
+                Search::Habitable => PlanetarySystem::generate_native_planets(&star),
+                // Life can arrive from elsewhere, so this needs the planets as the galaxy has shaped them
+                Search::Life => panspermia_generator::generate_planets(&self.galaxy, seed, self.elapsed_time),
+            };
+            for (index, planet) in planets.iter().enumerate() {
+                let found = match search {
+                    Search::Habitable => planet.get_habitability(),
+                    Search::Life => planet.has_life(),
+                };
+                if found {
+                    results.push(PlanetarySystem::describe_planet(index, planet).within(&[Level::Star(seed as usize)]));
+                }
             }
-            SceneName::PlanetarySystem => {
-                self.scene = Box::new(Galaxy::new(self.galaxy_size));
+        }
+        results
+    }
+
+    // Listens from the current star for technosignatures from civilizations around the others,
+    // keeping only those close enough to be picked up
+    pub fn scan(&mut self) -> String {
+        let Some(&Level::Star(home)) = self.path.first() else {
+            return "Travel to a star before listening for technosignatures.".to_string();
+        };
+        let mut home_star = self.galaxy.create_star(home as u64);
+        home_star.advance(self.elapsed_time);
+
+        let mut detections = Vec::new();
+        let mut sources = Vec::new();
+        for seed in 0..self.galaxy.get_size() {
+            if seed == home as u64 {
+                continue;
             }
-            SceneName::PlanetaryEnvironment => {
-                self.scene = Box::new(PlanetarySystem::new(&self.star_seed));
+            // Systems already built at this time are reused rather than built again
+            let star_path = [Level::Star(seed as usize)];
+            let (star, planets) = match self.scenes.get(&star_path[..]).and_then(|scene| scene.get_planetary_system()) {
+                Some(system) => (system.get_star().clone(), system.get_planets().clone()),
+                None => {
+                    let mut star = self.galaxy.create_star(seed);
+                    star.advance(self.elapsed_time);
+                    (star, panspermia_generator::generate_planets(&self.galaxy, seed, self.elapsed_time))
+                }
+            };
+            let distance = home_star.get_distance_to(&star);
+            let star_name = Galaxy::describe_star(seed as usize, &star).get_name().clone();
+
+            for (index, planet) in planets.iter().enumerate() {
+                if !Civilization::could_emerge(planet) {
+                    continue;
+                }
+                if let Some(civilization) = PlanetaryEnvironment::new(planet).get_civilization() {
+                    let path = Level::format_path(&[star_path[0].clone(), Level::Planet(index)]);
+                    for (signature, range) in civilization.get_technosignatures() {
+                        if distance <= range {
+                            detections.push(format!("{} from {} around {} [{}], {:.0} light years away",
+                                                    signature, planet.get_name(), star_name, path, distance));
+                            if !sources.contains(&planet.get_name()) {
+                                sources.push(planet.get_name());
+                            }
+                        }
+                    }
+                }
             }
         }
+
+        for source in sources {
+            self.discover(format!("Technosignatures from {}", source));
+        }
+
+        if detections.is_empty() {
+            "You listen, but the galaxy is silent.".to_string()
+        } else {
+            format!("You pick up:\n{}", detections.join("\n"))
+        }
+    }
+
+    // Fast-forwards the whole galaxy. Generation is deterministic for any age, so every cached
+    // scene is dropped and the current path retraced at the new age. Places change as planets
+    // do; if this one has vanished, we fall back as far as the path still goes.
+    pub fn advance(&mut self, time: u16) {
+        self.elapsed_time = self.elapsed_time.saturating_add(time);
+        self.reset_scenes();
+        let path = self.path.clone();
+        self.path = self.reach(&path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(path: &str) -> Option<String> {
+        Level::parse_path(path).map(|levels| Level::format_path(&levels))
+    }
+
+    #[test]
+    fn paths_read_from_the_galaxy_inward() {
+        assert!(Level::parse_path("/star/42/moon/3/region/1")
+            == Some(vec![Level::Star(42), Level::Moon(3), Level::Region(1)]));
+        assert_eq!(round_trip("/star/90/planet/1/organism/7").as_deref(), Some("/star/90/planet/1/organism/7"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn paths_forgive_case_and_stray_slashes() {
+        assert_eq!(round_trip("Star/5//PLANET/2/").as_deref(), Some("/star/5/planet/2"));
+        assert_eq!(round_trip("/").as_deref(), Some("/"));
+        assert_eq!(round_trip("").as_deref(), Some("/"));
+    }
+
+    #[test]
+    fn malformed_paths_are_refused() {
+        assert_eq!(round_trip("/star"), None);
+        assert_eq!(round_trip("/star/42/planet"), None);
+        assert_eq!(round_trip("/star/-1"), None);
+        assert_eq!(round_trip("/star/four"), None);
+        assert_eq!(round_trip("/comet/1"), None);
+    }
+}
@@ -0,0 +1,321 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::rc::Rc;
+
+use slint::{ComponentHandle, ModelRc, SharedString, VecModel};
+
+use crate::game_manager::{Game, Level, Property};
+use crate::star_map_manager;
+use ui::{Entry, Inspector, Row};
+
+// The generated code implements traits from inside functions, which newer compilers warn about
+#[allow(non_local_definitions)]
+mod ui {
+    slint::slint! {
+        import { Button, LineEdit, ListView, VerticalBox, HorizontalBox } from "std-widgets.slint";
+
+        export struct Row {
+            name: string,
+            value: string,
+        }
+
+        // A line in the navigation tree: either a scene on the path, or a unit of the current scene
+        export struct Entry {
+            label: string,
+            depth: int,
+            ancestor: bool,
+            target: int,
+            current: bool,
+        }
+
+        export component Inspector inherits Window {
+            title: "Astrobiology";
+            preferred-width: 1100px;
+            preferred-height: 720px;
+
+            in property <string> breadcrumbs;
+            in property <[Entry]> entries;
+            in property <string> heading;
+            in property <string> description;
+            in property <[Row]> rows;
+            in property <string> status;
+
+            callback activate(bool, int);
+            callback explore();
+            callback leave();
+            callback search(string);
+
+            VerticalBox {
+                Text {
+                    text: root.breadcrumbs;
+                    font-weight: 700;
+                }
+                HorizontalBox {
+                    VerticalBox {
+                        width: 360px;
+                        LineEdit {
+                            placeholder-text: "Filter, or a path such as /star/42/planet/3";
+                            accepted(query) => { root.search(query); }
+                        }
+                        ListView {
+                            for entry in root.entries : Rectangle {
+                                height: 24px;
+                                background: entry.current ? #5a6a9a80 : touch.has-hover ? #5a6a9a40 : transparent;
+                                Text {
+                                    x: 8px + entry.depth * 14px;
+                                    text: entry.label;
+                                    vertical-alignment: center;
+                                }
+                                touch := TouchArea {
+                                    clicked => { root.activate(entry.ancestor, entry.target); }
+                                }
+                            }
+                        }
+                        HorizontalBox {
+                            Button {
+                                text: "Explore";
+                                clicked => { root.explore(); }
+                            }
+                            Button {
+                                text: "Leave";
+                                clicked => { root.leave(); }
+                            }
+                        }
+                    }
+                    VerticalBox {
+                        Text {
+                            text: root.heading;
+                            font-size: 18px;
+                            font-weight: 700;
+                        }
+                        Text {
+                            text: root.description;
+                            wrap: word-wrap;
+                        }
+                        ListView {
+                            for row in root.rows : HorizontalLayout {
+                                height: 22px;
+                                spacing: 12px;
+                                Text {
+                                    text: row.name;
+                                    width: 280px;
+                                }
+                                Text {
+                                    text: row.value;
+                                }
+                            }
+                        }
+                        Text {
+                            text: root.status;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// What the inspector is showing besides the game: the unit picked out in the tree, if any, and
+// the filter typed into the search box
+struct InspectorState {
+    selected: Option<usize>,
+    filter: Option<String>,
+    status: String,
+}
+
+// Opens the inspector on the current scene. It steps in and out of the same game the command line
+// does, so closing it leaves the player wherever they were inspecting.
+pub fn run(game: &Rc<RefCell<Game>>) -> Result<(), Box<dyn Error>> {
+    if !star_map_manager::has_display() {
+        return Err("there is no display to open a window on".into());
+    }
+    open(game)?.run()?;
+    Ok(())
+}
+
+// Builds the inspector's window on whatever platform Slint runs on, sharing its event loop with
+// the map
+pub fn open(game: &Rc<RefCell<Game>>) -> Result<Inspector, Box<dyn Error>> {
+    let state = Rc::new(RefCell::new(InspectorState {
+        selected: None,
+        filter: None,
+        status: String::new(),
+    }));
+    let ui = Inspector::new()?;
+    refresh(&ui, &game.borrow(), &state.borrow());
+
+    // Scenes on the path are gone back to; units of the current scene are picked out
+    let (handle, shared_game, shared) = (ui.as_weak(), game.clone(), state.clone());
+    ui.on_activate(move |ancestor, target| {
+        let (mut game, mut state) = (shared_game.borrow_mut(), shared.borrow_mut());
+        if ancestor {
+            let path = game.get_path()[..target as usize].to_vec();
+            if path.len() < game.get_path().len() {
+                state.status = report(game.go_to(&path));
+                state.selected = None;
+                state.filter = None;
+            }
+        } else {
+            state.selected = Some(target as usize);
+        }
+        refresh(&handle.unwrap(), &game, &state);
+    });
+
+    let (handle, shared_game, shared) = (ui.as_weak(), game.clone(), state.clone());
+    ui.on_explore(move || {
+        let (mut game, mut state) = (shared_game.borrow_mut(), shared.borrow_mut());
+        match state.selected {
+            Some(index) => {
+                state.status = report(game.step_in(index));
+                state.selected = None;
+                state.filter = None;
+            }
+            None => state.status = "Pick something in the list to explore.".to_string(),
+        }
+        refresh(&handle.unwrap(), &game, &state);
+    });
+
+    let (handle, shared_game, shared) = (ui.as_weak(), game.clone(), state.clone());
+    ui.on_leave(move || {
+        let (mut game, mut state) = (shared_game.borrow_mut(), shared.borrow_mut());
+        state.status = report(game.step_out());
+        state.selected = None;
+        state.filter = None;
+        refresh(&handle.unwrap(), &game, &state);
+    });
+
+    // A path jumps straight there; anything else filters the units like LIST does
+    let (handle, shared_game, shared) = (ui.as_weak(), game.clone(), state.clone());
+    ui.on_search(move |query| {
+        let (mut game, mut state) = (shared_game.borrow_mut(), shared.borrow_mut());
+        let query = query.trim();
+        if query.starts_with('/') {
+            state.status = match Level::parse_path(query) {
+                Some(levels) => report(game.go_to(&levels)),
+                None => format!("{} is not a path. Paths look like /star/42/planet/3.", query),
+            };
+            state.filter = None;
+        } else {
+            state.status = String::new();
+            state.filter = if query.is_empty() { None } else { Some(query.to_string()) };
+        }
+        state.selected = None;
+        refresh(&handle.unwrap(), &game, &state);
+    });
+
+    Ok(ui)
+}
+
+fn report(result: Result<(), String>) -> String {
+    result.err().unwrap_or_default()
+}
+
+// Rebuilds every panel from the game: the path and units in the tree, and the selected unit, or
+// the scene itself, in the inspector
+fn refresh(ui: &Inspector, game: &Game, state: &InspectorState) {
+    let depth = game.get_path().len();
+    let units = game.get_units();
+
+    let mut entries: Vec<Entry> = game.get_scene_names().into_iter().enumerate()
+        .map(|(level, name)| Entry {
+            label: name.into(),
+            depth: level as i32,
+            ancestor: true,
+            target: level as i32,
+            current: level == depth && state.selected.is_none(),
+        })
+        .collect();
+    entries.extend(game.list(state.filter.as_deref()).iter().map(|unit| Entry {
+        label: format!("{} {}", unit.get_kind(), unit.get_name()).into(),
+        depth: depth as i32 + 1,
+        ancestor: false,
+        target: unit.get_index() as i32,
+        current: state.selected == Some(unit.get_index()),
+    }));
+
+    let (heading, description, mut properties) = match state.selected.and_then(|index| units.get(index)) {
+        Some(unit) => {
+            let kind = unit.get_path().last().map_or("", |level| level.get_kind());
+            (format!("{}: {}", capitalise(kind), unit.get_name()), unit.get_description().clone(),
+             unit.get_properties().clone())
+        }
+        None => {
+            let scene = game.get_scene();
+            (format!("{}: {}", scene.get_scene_name(), scene.get_name()), scene.get_description(),
+             scene.get_properties())
+        }
+    };
+    let path = match state.selected {
+        Some(index) => units.get(index).map_or(String::new(), |unit| unit.get_id()),
+        None => Level::format_path(game.get_path()),
+    };
+    properties.insert(0, Property::text("Path", path));
+    let rows: Vec<Row> = properties.iter()
+        .map(|property| Row { name: property.get_name().into(), value: property.get_value().to_string().into() })
+        .collect();
+
+    ui.set_breadcrumbs(game.get_breadcrumbs().into());
+    ui.set_entries(ModelRc::new(VecModel::from(entries)));
+    ui.set_heading(heading.into());
+    ui.set_description(description.into());
+    ui.set_rows(ModelRc::new(VecModel::from(rows)));
+    ui.set_status(SharedString::from(state.status.as_str()));
+}
+
+fn capitalise(word: &str) -> String {
+    let mut characters = word.chars();
+    match characters.next() {
+        Some(first) => first.to_uppercase().chain(characters).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    use slint::platform::software_renderer::{MinimalSoftwareWindow, RepaintBufferType};
+    use slint::platform::{Platform, WindowAdapter};
+    use slint::PlatformError;
+
+    use crate::galaxy_generator::GalaxySettings;
+    use crate::game_manager::Game;
+    use crate::star_map_manager;
+
+    // Stands in for the windowing system. Its event loop ends at once, as if the player closed
+    // each window as soon as it opened.
+    struct Headless {
+        windows: Rc<Cell<usize>>,
+    }
+
+    impl Platform for Headless {
+        fn create_window_adapter(&self) -> Result<Rc<dyn WindowAdapter>, PlatformError> {
+            self.windows.set(self.windows.get() + 1);
+            Ok(MinimalSoftwareWindow::new(RepaintBufferType::NewBuffer))
+        }
+
+        fn run_event_loop(&self) -> Result<(), PlatformError> {
+            Ok(())
+        }
+    }
+
+    // Either window building an event loop of its own would fail the second time round. Showing
+    // a window needs fonts the headless renderer lacks, so each is built and the shared event
+    // loop run in its place.
+    #[test]
+    fn map_and_inspector_reopen_in_one_session() {
+        let windows = Rc::new(Cell::new(0));
+        slint::platform::set_platform(Box::new(Headless { windows: windows.clone() })).unwrap();
+        let game = Rc::new(RefCell::new(Game::new(GalaxySettings::default())));
+        for _ in 0..2 {
+            let map = star_map_manager::open(&game).unwrap();
+            slint::run_event_loop().unwrap();
+            drop(map);
+            let inspector = super::open(&game).unwrap();
+            slint::run_event_loop().unwrap();
+            drop(inspector);
+        }
+        assert_eq!(windows.get(), 4);
+    }
+}
@@ -1,64 +1,82 @@
 mod game_manager;
+mod options_manager;
+mod export_manager;
+mod stats_manager;
+mod save_manager;
+mod command_manager;
+mod presentation_manager;
+mod star_map_manager;
+mod inspector_manager;
+mod tui_manager;
 mod galaxy_generator;
 mod planet_generator;
 mod planetary_system_generator;
 mod star_generator;
 mod planetary_environment_generator;
 mod organism_generator;
+mod body_plan_generator;
+mod name_generator;
+mod phylogeny_generator;
+mod food_web_generator;
+mod region_generator;
+mod silhouette_generator;
+mod civilization_generator;
+mod panspermia_generator;
+mod moon_generator;
+mod catastrophe_generator;
+mod biosphere_metrics_generator;
 
-use std::io;
-use std::io::Write;
+use std::cell::RefCell;
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::rc::Rc;
+use clap::Parser;
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::Editor;
 use crate::galaxy_generator::Galaxy;
 use crate::planetary_environment_generator::PlanetaryEnvironment;
 use crate::planetary_system_generator::PlanetarySystem;
-use crate::game_manager::{Game, SceneName};
+use crate::command_manager::{Command, CommandHelper};
+use crate::game_manager::{Game, Level};
+use crate::options_manager::{Frontend, Mode, Options};
+use crate::presentation_manager::{Format, Printer};
+use crate::silhouette_generator::Silhouette;
 
-const STARS_IN_GALAXY: u64 = 100;
+const NEWICK_EXPORT_PATH: &str = "phylogeny.nwk";
+const FIELD_GUIDE_DIRECTORY: &str = "field_guide";
+const SAVE_PATH: &str = "astrobiology.sav";
 
-enum Commands {
-    Coord(usize),
-    Exit,
-    Explore,
-    Leave,
-    Invalid,
-}
-
-fn parse_input() -> Commands {
-
-    println!();
-    print!("> ");
-    io::stdout().flush().unwrap();
-    let mut input = String::new();
-
-    io::stdin()
-        .read_line(&mut input)
-        .expect("Failed to read line.");
+// Reads the next command, with line editing and tab completion. Returns None once input runs out.
+// In JSON mode nothing but the objects themselves is printed, so the prompt and the blank lines
+// around it are left out.
+fn read_command(editor: &mut Editor<CommandHelper, DefaultHistory>, printer: &Printer) -> Option<Command> {
+    let text = printer.get_format() == Format::Text;
+    loop {
+        if text {
+            println!();
+        }
+        let line = match editor.readline(if text { "> " } else { "" }) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => return None,
+            Err(error) => {
+                printer.message(&format!("Could not read the command: {}", error));
+                return None;
+            }
+        };
 
-    // add newline after input
-    println!();
+        // add newline after input
+        if text {
+            println!();
+        }
 
-    match input.trim().parse::<usize>() {
-        Ok(index) => {
-            Commands::Coord(index)
+        if !line.trim().is_empty() {
+            let _ = editor.add_history_entry(line.trim());
         }
-        Err(_) => {
-            match &input.trim().to_lowercase() as &str {
-                "quit" => {
-                    Commands::Exit
-                }
-                "exit" => {
-                    Commands::Exit
-                }
-                "explore" => {
-                    Commands::Explore
-                }
-                "leave" => {
-                    Commands::Leave
-                }
-                _ => {
-                    Commands::Invalid
-                }
-            }
+        match command_manager::parse(&line) {
+            Ok(command) => return Some(command),
+            Err(error) => printer.message(&error),
         }
     }
 }
@@ -68,43 +86,289 @@ fn print_title_screen(){
     println!("A S T R O B I O L O G Y");
     println!("------------------------");
     println!();
-    println!("Enter a star's value to measure its properties. Type QUIT and ENTER to end game.");
+    println!("Type LIST to see the stars, INFO followed by a number to measure one, and EXPLORE \
+    followed by a number to travel there. Type HELP to see every command, and QUIT to end the game.");
+    println!("Press TAB to complete a command, and the arrow keys to recall earlier ones.");
+    println!();
+    println!("Somewhere in the galaxy, another species may have learned to use tools. Type SCAN \
+    at a star to listen for the technosignatures of civilizations around its neighbours, and find them.");
     println!();
 }
 
-fn main() {
+// One PNG per living species, named after it
+fn draw_field_guide(phylogeny: &phylogeny_generator::Phylogeny) -> Result<usize, Box<dyn std::error::Error>> {
+    fs::create_dir_all(FIELD_GUIDE_DIRECTORY)?;
+    let living = phylogeny.get_living();
+    for &lineage in &living {
+        let organism = phylogeny.get_lineage(lineage).unwrap().get_organism();
+        let path = Path::new(FIELD_GUIDE_DIRECTORY).join(format!("{}.png", organism.get_name().replace(' ', "_")));
+        Silhouette::new(organism).save_png(&path)?;
+    }
+    Ok(living.len())
+}
 
-    print_title_screen();
+// Carries out one command. Returns false once the player asks to stop.
+fn execute(shared: &Rc<RefCell<Game>>, command: Command, printer: &Printer, history: &[String]) -> bool {
+    let mut game = shared.borrow_mut();
+    match command {
+        Command::Info(index) => {
+            printer.unit(game.get_units().get(index));
+        }
+        Command::Explore(index) => {
+            match game.step_in(index) {
+                Ok(_) => printer.scene(&game),
+                Err(error) => printer.message(&error),
+            }
+        }
+        Command::Leave => {
+            match game.step_out() {
+                Ok(_) => printer.scene(&game),
+                Err(error) => printer.message(&error),
+            }
+        }
+        Command::Back => {
+            match game.back() {
+                Ok(_) => printer.scene(&game),
+                Err(error) => printer.message(&error),
+            }
+        }
+        Command::Goto(path) => {
+            match Level::parse_path(&path) {
+                Some(levels) => match game.go_to(&levels) {
+                    Ok(_) => printer.scene(&game),
+                    Err(error) => printer.message(&error),
+                },
+                None => printer.message(&format!("{} is not a path. Paths look like /star/42/planet/3.", path)),
+            }
+        }
+        Command::Advance(time) => {
+            game.advance(time);
+            printer.message(&format!("{} million years have passed since you arrived.", game.get_elapsed_time()));
+            printer.scene(&game);
+        }
+        Command::Tree => {
+            match game.get_scene().get_phylogeny() {
+                Some(phylogeny) if phylogeny.get_lineage_count() > 0 => {
+                    printer.message(&phylogeny.get_tree_info());
+                }
+                _ => printer.message("There is no tree of life here."),
+            }
+        }
+        Command::Clade(index) => {
+            match game.get_scene().get_phylogeny() {
+                Some(phylogeny) => printer.message(&phylogeny.get_clade_info(index)),
+                None => printer.message("There is no tree of life here."),
+            }
+        }
+        Command::Newick => {
+            match game.get_scene().get_phylogeny() {
+                Some(phylogeny) if phylogeny.get_lineage_count() > 0 => {
+                    let newick = phylogeny.to_newick();
+                    match fs::write(NEWICK_EXPORT_PATH, &newick) {
+                        Ok(_) => printer.message(&format!("{}\n\nSaved to {}.", newick, NEWICK_EXPORT_PATH)),
+                        Err(error) => printer.message(&format!("Could not save {}: {}", NEWICK_EXPORT_PATH, error)),
+                    }
+                }
+                _ => printer.message("There is no tree of life here."),
+            }
+        }
+        Command::Web => {
+            printer.food_web(game.get_scene().get_food_web().filter(|food_web| !food_web.get_species().is_empty()));
+        }
+        Command::Remove(index) => {
+            match game.get_scene_mut().get_food_web_mut() {
+                Some(food_web) => printer.message(&food_web.remove_species(index)),
+                None => printer.message("There is no food web here."),
+            }
+        }
+        Command::Draw => {
+            match game.get_scene().get_phylogeny() {
+                Some(phylogeny) if phylogeny.get_lineage_count() > 0 => {
+                    match draw_field_guide(phylogeny) {
+                        Ok(count) => printer.message(&format!("Drew {} species into {}.", count, FIELD_GUIDE_DIRECTORY)),
+                        Err(error) => printer.message(&format!("Could not draw the field guide: {}", error)),
+                    }
+                }
+                _ => printer.message("There is nothing living here to draw."),
+            }
+        }
+        Command::Map => {
+            // Like the inspector, the map's window shares the game while it is open
+            drop(game);
+            match star_map_manager::run(shared) {
+                Ok(_) => printer.scene(&shared.borrow()),
+                Err(error) => printer.message(&format!("Could not open the star map: {}", error)),
+            }
+        }
+        Command::Inspect => {
+            // The inspector's window shares the game while it is open, so it is let go of here
+            drop(game);
+            match inspector_manager::run(shared) {
+                Ok(_) => printer.scene(&shared.borrow()),
+                Err(error) => printer.message(&format!("Could not open the inspector: {}", error)),
+            }
+        }
+        Command::Scan => {
+            printer.message(&game.scan());
+        }
+        Command::Timeline => {
+            let scene = game.get_scene();
+            let present = scene.get_phylogeny().map_or(0, |phylogeny| phylogeny.get_present());
+            printer.timeline(scene.get_catastrophes().map(|catastrophes| (present, catastrophes.as_slice())));
+        }
+        Command::Save => {
+            match game.save(Path::new(SAVE_PATH)) {
+                Ok(_) => printer.message(&format!("Saved to {}.", SAVE_PATH)),
+                Err(error) => printer.message(&format!("Could not save {}: {}", SAVE_PATH, error)),
+            }
+        }
+        Command::Load => {
+            match Game::load(Path::new(SAVE_PATH)) {
+                Ok(loaded) => {
+                    *game = loaded;
+                    printer.message(&format!("Loaded {}. {} million years have passed since you arrived.",
+                                             SAVE_PATH, game.get_elapsed_time()));
+                    printer.scene(&game);
+                }
+                Err(error) => printer.message(&format!("Could not load {}: {}", SAVE_PATH, error)),
+            }
+        }
+        Command::Note(note) => {
+            game.add_note(note);
+            printer.message("Noted.");
+        }
+        Command::Journal => {
+            printer.message(&game.get_journal_info());
+        }
+        Command::List(filter) => {
+            printer.list(&game.list(filter.as_deref()), filter.is_some());
+        }
+        Command::Find(search) => {
+            printer.search(&game.find(&search));
+        }
+        Command::Compare(first, second) => {
+            printer.comparison(game.compare(first, second));
+        }
+        Command::History => {
+            let lines: Vec<String> = history.iter().enumerate()
+                .map(|(number, line)| format!("{:>4}  {}", number + 1, line))
+                .collect();
+            printer.message(&lines.join("\n"));
+        }
+        Command::Help(command) => {
+            printer.message(&command_manager::get_help(command.as_deref()));
+        }
+        Command::Exit => {
+            return false;
+        }
+    }
+    true
+}
 
-    let mut game: Game = Game::new(STARS_IN_GALAXY);
+fn run_repl(game: &Rc<RefCell<Game>>, printer: &Printer) {
+    if printer.get_format() == Format::Text {
+        print_title_screen();
+    }
 
-    let mut coord: usize = 0;
+    let mut editor: Editor<CommandHelper, DefaultHistory> = Editor::new().expect("Failed to start the command line.");
+    editor.set_helper(Some(CommandHelper));
 
-    println!("{}", game.scene.get_system_info());
+    printer.scene(&game.borrow());
 
-    loop {
-        let command: Commands = parse_input();
+    while let Some(command) = read_command(&mut editor, printer) {
+        let history: Vec<String> = editor.history().iter().cloned().collect();
+        if !execute(game, command, printer, &history) {
+            break;
+        }
+    }
+    printer.message("Thanks for playing!");
+}
 
-        match command {
-            Commands::Coord(index) => {
-                println!("{}", game.scene.get_unit_info(index));
-                coord = index;
+// Runs each line of a script as if it had been typed, echoing it first so the output reads like
+// a session. Blank lines and lines starting with # are skipped.
+fn run_script(game: &Rc<RefCell<Game>>, script: &str, printer: &Printer) {
+    printer.scene(&game.borrow());
+
+    let mut history = Vec::new();
+    for line in script.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        if printer.get_format() == Format::Text {
+            println!("\n> {}\n", line);
+        }
+        history.push(line.to_string());
+        match command_manager::parse(line) {
+            Ok(command) => {
+                if !execute(game, command, printer, &history) {
+                    break;
+                }
             }
-            Commands::Explore => {
-                game.step_in(coord);
-                println!("{}", game.scene.get_system_info());
+            Err(error) => printer.message(&error),
+        }
+    }
+}
+
+fn main() {
+    let options = Options::parse();
+    let printer = Printer::new(options.get_format());
+
+    // The other modes work on the galaxy without playing
+    if let Some(mode) = options.get_mode() {
+        let result = match mode {
+            Mode::Export { directory, table_format, panspermia } => {
+                export_manager::run(&options.get_galaxy(), directory, *table_format, *panspermia)
+                    .map_err(|error| format!("Could not export the galaxy: {}", error))
             }
-            Commands::Leave => {
-                game.step_out();
-                println!("{}", game.scene.get_system_info());
+            Mode::Stats { sample, output } => {
+                stats_manager::run(&options.get_galaxy(), *sample, output)
+                    .map_err(|error| format!("Could not gather the statistics: {}", error))
             }
-            Commands::Exit => {
-                break;
+        };
+        match result {
+            Ok(summary) => println!("{}", summary),
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
             }
-            Commands::Invalid => {
-                println!("Invalid coordinates.")
+        }
+        return;
+    }
+
+    let game = Rc::new(RefCell::new(Game::new(options.get_galaxy())));
+
+    if let Some(start) = options.get_start() {
+        let result = Level::parse_path(start)
+            .ok_or_else(|| format!("{} is not a path. Paths look like /star/42/planet/3.", start))
+            .and_then(|levels| game.borrow_mut().go_to(&levels));
+        if let Err(error) = result {
+            eprintln!("{}", error);
+            process::exit(2);
+        }
+    }
+
+    if let Some(script) = options.get_script() {
+        match fs::read_to_string(script) {
+            Ok(contents) => run_script(&game, &contents, &printer),
+            Err(error) => {
+                eprintln!("Could not read {}: {}", script.display(), error);
+                process::exit(1);
             }
         }
+        return;
     }
-    println!("Thanks for playing!");
-}
\ No newline at end of file
+
+    match options.get_frontend() {
+        Frontend::Repl => run_repl(&game, &printer),
+        // The full-screen interfaces take the place of the command line entirely
+        Frontend::Tui => match tui_manager::run(&mut game.borrow_mut()) {
+            Ok(_) => println!("Thanks for playing!"),
+            Err(error) => eprintln!("Could not start the terminal interface: {}", error),
+        },
+        Frontend::Gui => match star_map_manager::run(&game) {
+            Ok(_) => println!("Thanks for playing!"),
+            Err(error) => eprintln!("Could not open the star map: {}", error),
+        },
+        Frontend::Inspector => match inspector_manager::run(&game) {
+            Ok(_) => println!("Thanks for playing!"),
+            Err(error) => eprintln!("Could not open the inspector: {}", error),
+        },
+    }
+}
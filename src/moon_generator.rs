@@ -0,0 +1,128 @@
+use rand::prelude::*;
+use strum_macros::Display;
+
+use crate::game_manager::{Property, Scene, SceneName};
+use crate::name_generator;
+use crate::planet_generator;
+use crate::planet_generator::PlanetClass;
+
+const MOON_SEED_OFFSET: u64 = 32452867; // keeps moons independent of the planet's own rolls
+const MOON_SEED_MULTIPLIER: u64 = 1_000_003;
+const MAXIMUM_MASS_FRACTION: f64 = 0.02; // of the planet's mass
+const MINIMUM_ORBITAL_RADIUS: f64 = 3.0; // in planetary radii; any closer and tides tear a moon apart
+const MAXIMUM_ORBITAL_RADIUS: f64 = 300.0; // in planetary radii
+const TIDAL_LOCKING_RADIUS: f64 = 100.0; // in planetary radii; closer moons keep one face to their planet
+const TIDAL_HEATING_RADIUS: f64 = 20.0; // in planetary radii; closer moons are flexed warm inside
+const ICE_LINE_TEMPERATURE: u16 = 170; // in kelvins; colder moons are built mostly of ice
+
+#[derive(Display, Clone, PartialEq)]
+pub enum MoonComposition {
+    Rocky,
+    Icy,
+}
+
+#[derive(Clone)]
+pub struct Moon {
+    designation: String,
+    mass: u32, // in the same units as planets
+    orbital_radius: f64, // in planetary radii
+    composition: MoonComposition,
+    tidally_locked: bool,
+    subsurface_ocean: bool, // kept liquid under the ice by tidal heating
+}
+
+impl Moon {
+    pub fn new(planet: &planet_generator::Planet, index: usize) -> Moon {
+        let mut rng = StdRng::seed_from_u64(planet.get_seed().wrapping_add(MOON_SEED_OFFSET)
+            .wrapping_mul(MOON_SEED_MULTIPLIER).wrapping_add(index as u64));
+
+        let mass = ((planet.get_mass() as f64 * rng.gen_range(0.0..MAXIMUM_MASS_FRACTION)) as u32).max(1);
+        let orbital_radius = rng.gen_range(MINIMUM_ORBITAL_RADIUS..MAXIMUM_ORBITAL_RADIUS);
+        // Giants form in the cold outer system, where their moons gather ice. Moons form with their
+        // planet, before anything could have changed its climate.
+        let cold = planet.get_temperature_at(0) < ICE_LINE_TEMPERATURE
+            || matches!(planet.get_class(), PlanetClass::GasGiant | PlanetClass::IceGiant);
+        let composition = if cold { MoonComposition::Icy } else { MoonComposition::Rocky };
+        let subsurface_ocean = composition == MoonComposition::Icy && orbital_radius < TIDAL_HEATING_RADIUS;
+
+        Moon {
+            designation: name_generator::generate_moon_designation(planet.get_designation(), index),
+            mass,
+            orbital_radius,
+            composition,
+            tidally_locked: orbital_radius < TIDAL_LOCKING_RADIUS,
+            subsurface_ocean,
+        }
+    }
+
+    pub fn get_info(&self) -> String {
+        let rotation = if self.tidally_locked { "keeps one face turned to its planet" } else { "turns freely" };
+        let ocean = if self.subsurface_ocean { " Tidal heating keeps an ocean liquid beneath its ice." } else { "" };
+        format!("This is {}, a {} moon of mass {} orbiting {:.0} planetary radii out. It {}.{}",
+                self.designation, self.composition, self.mass, self.orbital_radius, rotation, ocean)
+    }
+
+    pub fn get_designation(&self) -> &String {
+        &self.designation
+    }
+
+    pub fn get_mass(&self) -> u32 {
+        self.mass
+    }
+
+    pub fn get_orbital_radius(&self) -> f64 {
+        self.orbital_radius
+    }
+
+    pub fn get_composition(&self) -> &MoonComposition {
+        &self.composition
+    }
+
+    pub fn is_tidally_locked(&self) -> bool {
+        self.tidally_locked
+    }
+
+    pub fn has_subsurface_ocean(&self) -> bool {
+        self.subsurface_ocean
+    }
+}
+
+// The measurements shared by a moon's own scene and the export
+pub fn describe_moon(moon: &Moon) -> Vec<Property> {
+    vec![
+        Property::text("Composition", &moon.composition),
+        Property::integer("Mass", moon.mass),
+        Property::number("Orbital radius (planetary radii)", moon.orbital_radius),
+        Property::flag("Tidally locked", moon.tidally_locked),
+        Property::flag("Subsurface ocean", moon.subsurface_ocean),
+    ]
+}
+
+// A moon is the end of the line; there is nothing further in to explore
+pub struct MoonEnvironment {
+    moon: Moon,
+}
+
+impl MoonEnvironment {
+    pub fn new(moon: Moon) -> MoonEnvironment {
+        MoonEnvironment { moon }
+    }
+}
+
+impl Scene for MoonEnvironment {
+    fn get_scene_name(&self) -> SceneName {
+        SceneName::Moon
+    }
+
+    fn get_name(&self) -> String {
+        self.moon.get_designation().clone()
+    }
+
+    fn get_description(&self) -> String {
+        self.moon.get_info()
+    }
+
+    fn get_properties(&self) -> Vec<Property> {
+        describe_moon(&self.moon)
+    }
+}
@@ -0,0 +1,134 @@
+use rand::prelude::*;
+
+const ONSETS: [&str; 24] = [
+    "b", "c", "d", "f", "g", "h", "k", "l", "m", "n", "p", "r", "s", "t", "v", "z",
+    "br", "cr", "dr", "gr", "tr", "th", "st", "ph",
+];
+const VOWELS: [&str; 9] = ["a", "e", "i", "o", "u", "ae", "ia", "eo", "y"];
+const CODAS: [&str; 10] = ["r", "l", "n", "s", "t", "x", "d", "m", "ph", "th"];
+const GENUS_ENDINGS: [&str; 9] = ["us", "a", "um", "is", "ia", "on", "ops", "ella", "ium"];
+const EPITHET_ENDINGS: [&str; 9] = ["i", "ae", "ensis", "ata", "oides", "ica", "alis", "ens", "iformis"];
+const CODA_CHANCE: f64 = 0.3; // for the last syllable of a proper name
+const CATALOG_PREFIX: &str = "HD";
+const CATALOG_NUMBER_MINIMUM: u64 = 100000;
+const CATALOG_NUMBER_RANGE: u64 = 900000;
+const CATALOG_NUMBER_STRIDE: u64 = 7919; // prime, so every star below the range gets its own number
+const ROMAN_NUMERALS: [(usize, &str); 13] = [
+    (1000, "M"), (900, "CM"), (500, "D"), (400, "CD"), (100, "C"), (90, "XC"),
+    (50, "L"), (40, "XL"), (10, "X"), (9, "IX"), (5, "V"), (4, "IV"), (1, "I"),
+];
+
+// Syllables are a consonant onset followed by a vowel, which keeps every word pronounceable
+fn generate_syllables(rng: &mut impl Rng, count: usize) -> String {
+    let mut word = String::new();
+    for _ in 0..count {
+        word.push_str(ONSETS.choose(rng).unwrap());
+        word.push_str(VOWELS.choose(rng).unwrap());
+    }
+    word
+}
+
+fn capitalize(word: &str) -> String {
+    let mut letters = word.chars();
+    match letters.next() {
+        Some(first) => first.to_uppercase().chain(letters).collect(),
+        None => String::new(),
+    }
+}
+
+// A Latinate genus name such as "Travelops"
+pub fn generate_genus(seed: u64) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let syllables = rng.gen_range(1..=2);
+    let mut genus = generate_syllables(&mut rng, syllables);
+    genus.push_str(CODAS.choose(&mut rng).unwrap());
+    genus.push_str(GENUS_ENDINGS.choose(&mut rng).unwrap());
+    capitalize(&genus)
+}
+
+// The lowercase second half of a binomial, such as "dromensis"
+pub fn generate_epithet(seed: u64) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let syllables = rng.gen_range(1..=2);
+    let mut epithet = generate_syllables(&mut rng, syllables);
+    epithet.push_str(CODAS.choose(&mut rng).unwrap());
+    epithet.push_str(EPITHET_ENDINGS.choose(&mut rng).unwrap());
+    epithet
+}
+
+// A proper name for a star or planet, such as "Veloran"
+pub fn generate_proper_name(seed: u64) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let syllables = rng.gen_range(2..=3);
+    let mut name = generate_syllables(&mut rng, syllables);
+    if rng.gen_bool(CODA_CHANCE) {
+        name.push_str(CODAS.choose(&mut rng).unwrap());
+    }
+    capitalize(&name)
+}
+
+// A star catalog designation such as "HD 215634", scrambled so that neighbouring stars don't get
+// neighbouring numbers
+pub fn generate_catalog_designation(star_seed: u64) -> String {
+    let number = CATALOG_NUMBER_MINIMUM
+        + star_seed.wrapping_mul(CATALOG_NUMBER_STRIDE) % CATALOG_NUMBER_RANGE;
+    format!("{} {}", CATALOG_PREFIX, number)
+}
+
+// Planets take their star's designation and a letter by distance, starting at "b"
+pub fn generate_planet_designation(star_designation: &str, distance: u8) -> String {
+    format!("{} {}", star_designation, (b'a' + distance) as char)
+}
+
+// Moons are numbered outward from their planet in Roman numerals, starting at I
+pub fn generate_moon_designation(planet_designation: &str, index: usize) -> String {
+    let mut number = index + 1;
+    let mut numeral = String::new();
+    for (value, symbol) in ROMAN_NUMERALS {
+        while number >= value {
+            numeral.push_str(symbol);
+            number -= value;
+        }
+    }
+    format!("{} {}", planet_designation, numeral)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn the_same_seed_always_gives_the_same_name() {
+        for seed in 0..1000 {
+            assert_eq!(generate_genus(seed), generate_genus(seed));
+            assert_eq!(generate_epithet(seed), generate_epithet(seed));
+            assert_eq!(generate_proper_name(seed), generate_proper_name(seed));
+        }
+    }
+
+    #[test]
+    fn names_vary_with_the_seed_and_are_cased_by_kind() {
+        let genera: HashSet<String> = (0..1000).map(generate_genus).collect();
+        let epithets: HashSet<String> = (0..1000).map(generate_epithet).collect();
+        assert!(genera.len() > 900 && epithets.len() > 900);
+        assert!(genera.iter().all(|genus| genus.starts_with(|letter: char| letter.is_uppercase())));
+        assert!(epithets.iter().all(|epithet| epithet.chars().all(|letter| letter.is_lowercase())));
+    }
+
+    #[test]
+    fn every_star_below_the_range_has_its_own_catalog_number() {
+        assert_eq!(generate_catalog_designation(0), "HD 100000");
+        assert_eq!(generate_catalog_designation(1), "HD 107919");
+        let designations: HashSet<String> = (0..CATALOG_NUMBER_RANGE).map(generate_catalog_designation).collect();
+        assert_eq!(designations.len() as u64, CATALOG_NUMBER_RANGE);
+    }
+
+    #[test]
+    fn planets_are_lettered_and_moons_numbered() {
+        assert_eq!(generate_planet_designation("HD 812710", 1), "HD 812710 b");
+        assert_eq!(generate_moon_designation("HD 812710 c", 0), "HD 812710 c I");
+        assert_eq!(generate_moon_designation("HD 812710 c", 3), "HD 812710 c IV");
+        assert_eq!(generate_moon_designation("HD 812710 c", 13), "HD 812710 c XIV");
+    }
+}
@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use strum_macros::{Display, EnumString};
+
+use crate::export_manager::ExportFormat;
+use crate::galaxy_generator::{GalaxySettings, GalaxyShape};
+use crate::presentation_manager::Format;
+
+const STARS_IN_GALAXY: u64 = 100;
+const STARS_IN_SAMPLE: u64 = 10_000;
+
+#[derive(Display, EnumString, Clone, Copy, PartialEq)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum Frontend {
+    Repl, // the command line
+    Tui, // the full-screen terminal interface
+    Gui, // the star map
+    Inspector, // the window listing every place and its properties
+}
+
+// What to do instead of playing, for working with generated galaxies in other tools
+#[derive(Subcommand)]
+pub enum Mode {
+    /// Write every star, planet, moon and species of the galaxy, with its food webs, biospheres and
+    /// catastrophes, to tables, without playing
+    Export {
+        /// The directory the tables are written into
+        #[arg(long, default_value = "export")]
+        directory: PathBuf,
+
+        /// Which files the tables are written as: jsonl, csv or both
+        #[arg(long, default_value_t = ExportFormat::Jsonl)]
+        table_format: ExportFormat,
+
+        /// Let life travel between stars, as it does in play; slow for large galaxies
+        #[arg(long)]
+        panspermia: bool,
+    },
+
+    /// Sample the galaxy and report how its stars, planets and species are distributed
+    Stats {
+        /// How many stars to sample, from the first star of the galaxy on
+        #[arg(long, default_value_t = STARS_IN_SAMPLE)]
+        sample: u64,
+
+        /// The file the figures are also written to, as JSON
+        #[arg(long, default_value = "stats.json")]
+        output: PathBuf,
+    },
+}
+
+// Everything that can be chosen when the game starts. The same options always give the same
+// session, so they are all a bug report needs.
+#[derive(Parser)]
+#[command(name = "astrobiology", version,
+          about = "An exploration game that procedurally generates alien worlds and ecosystems.")]
+pub struct Options {
+    /// Which galaxy to generate; the same seed always gives the same galaxy
+    #[arg(long, global = true, default_value_t = 0)]
+    seed: u64,
+
+    /// How many stars the galaxy holds [default: 100, or as many as stats samples]
+    #[arg(long, global = true)]
+    stars: Option<u64>,
+
+    /// How the stars are spread out: disc, spiral, elliptical or ring
+    #[arg(long, global = true, default_value_t = GalaxyShape::Disc)]
+    shape: GalaxyShape,
+
+    /// Where to begin, as a path such as /star/42/planet/3
+    #[arg(long, value_name = "PATH")]
+    start: Option<String>,
+
+    /// How to play: repl, tui, gui (the star map) or inspector
+    #[arg(long, default_value_t = Frontend::Repl)]
+    frontend: Frontend,
+
+    /// Shorthand for --frontend tui
+    #[arg(long, conflicts_with = "frontend")]
+    tui: bool,
+
+    /// How results are printed: text, or json with one object per line
+    #[arg(long, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Run the commands in this file, one per line, then exit
+    #[arg(long, value_name = "FILE")]
+    script: Option<PathBuf>,
+
+    #[command(subcommand)]
+    mode: Option<Mode>,
+}
+
+impl Options {
+    // Stats sample from the first star on, so unless told otherwise the galaxy is just big enough
+    pub fn get_galaxy(&self) -> GalaxySettings {
+        let stars = match &self.mode {
+            Some(Mode::Stats { sample, .. }) => self.stars.unwrap_or(*sample),
+            _ => self.stars.unwrap_or(STARS_IN_GALAXY),
+        };
+        GalaxySettings::new(self.seed, stars, self.shape)
+    }
+
+    pub fn get_start(&self) -> Option<&String> {
+        self.start.as_ref()
+    }
+
+    pub fn get_frontend(&self) -> Frontend {
+        if self.tui { Frontend::Tui } else { self.frontend }
+    }
+
+    pub fn get_format(&self) -> Format {
+        self.format
+    }
+
+    pub fn get_script(&self) -> Option<&PathBuf> {
+        self.script.as_ref()
+    }
+
+    pub fn get_mode(&self) -> Option<&Mode> {
+        self.mode.as_ref()
+    }
+}
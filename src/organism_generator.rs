@@ -1,23 +1,39 @@
 use strum_macros::Display;
 use rand::prelude::*;
-use rand::distributions::{Alphanumeric, DistString};
 
+use crate::body_plan_generator::{Appendage, BodyPlan};
+use crate::name_generator;
 use crate::planet_generator;
 use crate::planet_generator::Ocean;
 
-#[derive(Display)]
+const MUTATION_CHANCE: f64 = 0.2; // per trait, each time a new species branches off
+const MULTICELLULARITY_TIME: u16 = 2500; // in millions of years after the origin of life
+const MULTICELLULARITY_CHANCE: f64 = 0.5;
+const UNITARY_CHANCE: f64 = 0.5; // multicellular organisms are otherwise modular colonies
+const AEROBIC_CHANCE: f64 = 0.7; // once there is free oxygen to breathe
+const SILOXANE_TEMPERATURE: u16 = 350; // in kelvins; C chains break apart above this
+const HYDROGEN_RETENTION_MASS: u32 = 6000; // lighter water worlds lose their hydrogen to space
+const LAND_COLONIZATION_TIME: u16 = 3000; // in millions of years after the origin of life
+const MINIMUM_PRESSURE_FOR_FLIGHT: u32 = 100000; // thinner air can't hold a flyer aloft
+const AUTOTROPH_CHANCE: f64 = 0.4; // for descendants; the first life must make its own food
+const PHOTOTROPH_CHANCE: f64 = 0.5; // among autotrophs with access to starlight
+const PHOTORECEPTION_CHANCE: f64 = 0.6;
+const ELECTRORECEPTION_CHANCE: f64 = 0.2; // only conductive water carries electric fields
+const MINIMUM_LEGS_FOR_HANDS: u8 = 4; // a body needs a pair free of walking to hold tools
+
+#[derive(Display, Clone, PartialEq)]
 pub enum Size {
     SingleCell,
     MultiCellular,
 }
 
-#[derive(Display)]
+#[derive(Display, Clone, PartialEq)]
 pub enum Organization {
     Modular,
     Unitary,
 }
 
-#[derive(Display)]
+#[derive(Display, Clone, PartialEq)]
 pub enum Symmetry {
     Asymmetrical,
     Spherical,
@@ -28,69 +44,425 @@ pub enum Symmetry {
 // Terrestrial life is made of C-H chains
 // C-O chains will form in environments poor in H
 // S-O chains will form in high temperatures that prevent C chains from forming
-#[derive(Display)]
+#[derive(Display, Clone, PartialEq)]
 pub enum Structure {
     CarbonHydrogen,
     Oxocarbon,
     Siloxane,
 }
 
-#[derive(Display)]
+#[derive(Display, Clone, PartialEq)]
 pub enum Solvent {
     Water,
     Ammonia,
 }
 
-#[derive(Display)]
+#[derive(Display, Clone, PartialEq)]
 pub enum Metabolism {
     Aerobic,
     Anaerobic,
 }
 
+#[derive(Display, Clone, PartialEq)]
+pub enum EnergySource {
+    Phototroph,
+    Chemotroph,
+    Heterotroph,
+}
+
+#[derive(Display, Clone, PartialEq)]
+pub enum TrophicRole {
+    Producer,
+    Grazer,
+    Predator,
+    Decomposer,
+    Parasite,
+}
+
+#[derive(Display, Clone, PartialEq)]
+pub enum Reproduction {
+    Fission,
+    Budding,
+    Spores,
+    Sexual,
+}
+
+#[derive(Display, Clone, PartialEq)]
+pub enum Habitat {
+    Pelagic,
+    Benthic,
+    Aerial,
+    Terrestrial,
+    Subsurface,
+}
+
+#[derive(Display, Clone, PartialEq)]
+pub enum Locomotion {
+    Sessile,
+    Drifting,
+    Flagella,
+    Swimming,
+    Crawling,
+    Burrowing,
+    Walking,
+    Flying,
+}
+
+#[derive(Display, Clone, PartialEq)]
+pub enum Sense {
+    Chemoreception,
+    Photoreception,
+    Mechanoreception,
+    Electroreception,
+    Thermoreception,
+}
+
+#[derive(Clone)]
 pub struct Organism {
     name: String,
+    genus: String, // shared with the rest of the clade the species belongs to
     size: Size,
     organization: Organization,
     symmetry: Symmetry,
     structure: Structure,
     solvent: Solvent,
     metabolism: Metabolism,
+    energy_source: EnergySource,
+    trophic_role: TrophicRole,
+    reproduction: Reproduction,
+    habitat: Habitat,
+    locomotion: Locomotion,
+    senses: Vec<Sense>,
+    body_plan: BodyPlan,
 }
 
 impl Organism {
-    pub fn new (planet: &planet_generator::Planet, seed: u64) -> Organism {
+    // Traits are chosen from the planet's conditions at the time the species appears
+    pub fn new (planet: &planet_generator::Planet, time: u16, seed: u64) -> Organism {
 
         let mut rng = StdRng::seed_from_u64(seed);
 
-        let solvent = match planet.get_ocean() {
+        let solvent = match planet.get_ocean_at(time) {
             Ocean::Water => { Solvent::Water }
             Ocean::Ammonia => { Solvent::Ammonia }
             Ocean::None => {
                 panic!("Should not have been able to enter organism_generator without an ocean!") }
         };
 
-        Organism {
-            name: Alphanumeric.sample_string(&mut rng, 5),
-            size: Size::SingleCell,
-            organization: Organization::Modular,
-            symmetry: Symmetry::Asymmetrical,
-            structure: Structure::CarbonHydrogen,
+        let genus = name_generator::generate_genus(rng.gen());
+        let name = format!("{} {}", genus, name_generator::generate_epithet(rng.gen()));
+        let size = Self::choose_size(planet, time, &mut rng);
+        let organization = Self::choose_organization(&size, &mut rng);
+        let symmetry = Self::choose_symmetry(&size, &organization, &mut rng);
+        let metabolism = Self::choose_metabolism(planet, time, &mut rng);
+        let habitat = Self::choose_habitat(planet, time, &size, &organization, &mut rng);
+        let energy_source = Self::choose_autotrophy(&habitat, &mut rng);
+        let reproduction = Self::choose_reproduction(&size, &organization, &mut rng);
+        let locomotion = Self::choose_locomotion(
+            &size, &organization, &habitat, &TrophicRole::Producer, &mut rng,
+        );
+        let senses = Self::choose_senses(&habitat, &locomotion, &energy_source, &mut rng);
+
+        let mut organism = Organism {
+            name,
+            genus,
+            size,
+            organization,
+            symmetry,
+            structure: Self::choose_structure(planet, time),
             solvent,
-            metabolism: Metabolism::Anaerobic,
+            metabolism,
+            energy_source,
+            trophic_role: TrophicRole::Producer,
+            reproduction,
+            habitat,
+            locomotion,
+            senses,
+            body_plan: BodyPlan::default(),
+        };
+        organism.body_plan = BodyPlan::new(planet, time, &organism, &mut rng);
+        organism
+    }
+
+    // A new species branching off from this one inherits its traits, some of them mutated within
+    // what the planet allows at the time it appears
+    pub fn descend (&self, planet: &planet_generator::Planet, time: u16, seed: u64) -> Organism {
+
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let size = if rng.gen_bool(MUTATION_CHANCE) {
+            Self::choose_size(planet, time, &mut rng)
+        } else {
+            self.size.clone()
+        };
+        // A change in size reorganizes the whole body plan
+        let organization = if size != self.size || rng.gen_bool(MUTATION_CHANCE) {
+            Self::choose_organization(&size, &mut rng)
+        } else {
+            self.organization.clone()
+        };
+        let symmetry = if organization != self.organization || rng.gen_bool(MUTATION_CHANCE) {
+            Self::choose_symmetry(&size, &organization, &mut rng)
+        } else {
+            self.symmetry.clone()
+        };
+        let metabolism = if rng.gen_bool(MUTATION_CHANCE) {
+            Self::choose_metabolism(planet, time, &mut rng)
+        } else {
+            self.metabolism.clone()
+        };
+        let body_changed = size != self.size || organization != self.organization;
+        let habitat = if body_changed || rng.gen_bool(MUTATION_CHANCE) {
+            Self::choose_habitat(planet, time, &size, &organization, &mut rng)
+        } else {
+            self.habitat.clone()
+        };
+        // Photosynthesis is impossible once a lineage moves underground
+        let lost_light = habitat == Habitat::Subsurface && self.energy_source == EnergySource::Phototroph;
+        let (energy_source, trophic_role) = if lost_light || rng.gen_bool(MUTATION_CHANCE) {
+            Self::choose_diet(&habitat, &mut rng)
+        } else {
+            (self.energy_source.clone(), self.trophic_role.clone())
+        };
+        let reproduction = if body_changed || rng.gen_bool(MUTATION_CHANCE) {
+            Self::choose_reproduction(&size, &organization, &mut rng)
+        } else {
+            self.reproduction.clone()
+        };
+        let lifestyle_changed = body_changed || habitat != self.habitat || trophic_role != self.trophic_role;
+        let locomotion = if lifestyle_changed || rng.gen_bool(MUTATION_CHANCE) {
+            Self::choose_locomotion(&size, &organization, &habitat, &trophic_role, &mut rng)
+        } else {
+            self.locomotion.clone()
+        };
+        let senses = if lifestyle_changed || locomotion != self.locomotion || rng.gen_bool(MUTATION_CHANCE) {
+            Self::choose_senses(&habitat, &locomotion, &energy_source, &mut rng)
+        } else {
+            self.senses.clone()
+        };
+
+        // A species that takes up a new body or way of life founds a genus of its own; the rest
+        // stay in their parent's genus
+        let genus = if lifestyle_changed {
+            name_generator::generate_genus(rng.gen())
+        } else {
+            self.genus.clone()
+        };
+        let name = format!("{} {}", genus, name_generator::generate_epithet(rng.gen()));
+
+        let mut organism = Organism {
+            name,
+            genus,
+            size,
+            organization,
+            symmetry,
+            // All life on a planet shares its common ancestor's biochemistry
+            structure: self.structure.clone(),
+            solvent: self.solvent.clone(),
+            metabolism,
+            energy_source,
+            trophic_role,
+            reproduction,
+            habitat,
+            locomotion,
+            senses,
+            body_plan: self.body_plan.clone(),
+        };
+        // A new shape, way of life or set of senses calls for a body rebuilt to suit it
+        let reshaped = lifestyle_changed || organism.symmetry != self.symmetry
+            || organism.locomotion != self.locomotion || organism.senses != self.senses;
+        if reshaped || rng.gen_bool(MUTATION_CHANCE) {
+            organism.body_plan = BodyPlan::new(planet, time, &organism, &mut rng);
+        }
+        organism
+    }
+
+    fn calculate_time_since_origin (planet: &planet_generator::Planet, time: u16) -> u16 {
+        planet.get_origin_of_life().map_or(0, |origin| time.saturating_sub(origin))
+    }
+
+    fn choose_size (planet: &planet_generator::Planet, time: u16, rng: &mut impl Rng) -> Size {
+        if Self::calculate_time_since_origin(planet, time) >= MULTICELLULARITY_TIME && rng.gen_bool(MULTICELLULARITY_CHANCE) {
+            Size::MultiCellular
+        } else {
+            Size::SingleCell
         }
     }
 
+    fn choose_organization (size: &Size, rng: &mut impl Rng) -> Organization {
+        match size {
+            Size::SingleCell => Organization::Unitary,
+            Size::MultiCellular => {
+                if rng.gen_bool(UNITARY_CHANCE) { Organization::Unitary } else { Organization::Modular }
+            }
+        }
+    }
+
+    // Cells are round or irregular; colonies grow irregularly or radially; unitary bodies that
+    // move through their environment tend towards bilateral symmetry
+    fn choose_symmetry (size: &Size, organization: &Organization, rng: &mut impl Rng) -> Symmetry {
+        match (size, organization) {
+            (Size::SingleCell, _) => {
+                if rng.gen_bool(0.5) { Symmetry::Spherical } else { Symmetry::Asymmetrical }
+            }
+            (Size::MultiCellular, Organization::Modular) => {
+                if rng.gen_bool(0.5) { Symmetry::Radial } else { Symmetry::Asymmetrical }
+            }
+            (Size::MultiCellular, Organization::Unitary) => {
+                if rng.gen_bool(0.25) { Symmetry::Radial } else { Symmetry::Bilateral }
+            }
+        }
+    }
+
+    fn choose_structure (planet: &planet_generator::Planet, time: u16) -> Structure {
+        if planet.get_temperature_at(time) >= SILOXANE_TEMPERATURE {
+            Structure::Siloxane
+        } else if *planet.get_ocean_at(time) == Ocean::Water && planet.get_mass() < HYDROGEN_RETENTION_MASS {
+            Structure::Oxocarbon
+        } else {
+            Structure::CarbonHydrogen
+        }
+    }
+
+    fn choose_metabolism (planet: &planet_generator::Planet, time: u16, rng: &mut impl Rng) -> Metabolism {
+        if planet.has_free_oxygen_at(time) && rng.gen_bool(AEROBIC_CHANCE) {
+            Metabolism::Aerobic
+        } else {
+            Metabolism::Anaerobic
+        }
+    }
+
+    // Life spreads onto land and into the air only after it has had time to adapt, and only large
+    // unitary bodies can fly
+    fn choose_habitat (planet: &planet_generator::Planet, time: u16, size: &Size,
+                       organization: &Organization, rng: &mut impl Rng) -> Habitat {
+        let mut habitats = vec![Habitat::Pelagic, Habitat::Benthic, Habitat::Subsurface];
+        if Self::calculate_time_since_origin(planet, time) >= LAND_COLONIZATION_TIME {
+            habitats.push(Habitat::Terrestrial);
+            if *size == Size::MultiCellular && *organization == Organization::Unitary
+                && planet.get_pressure_at(time) >= MINIMUM_PRESSURE_FOR_FLIGHT {
+                habitats.push(Habitat::Aerial);
+            }
+        }
+        habitats.choose(rng).unwrap().clone()
+    }
+
+    fn choose_autotrophy (habitat: &Habitat, rng: &mut impl Rng) -> EnergySource {
+        if *habitat != Habitat::Subsurface && rng.gen_bool(PHOTOTROPH_CHANCE) {
+            EnergySource::Phototroph
+        } else {
+            EnergySource::Chemotroph
+        }
+    }
+
+    fn choose_diet (habitat: &Habitat, rng: &mut impl Rng) -> (EnergySource, TrophicRole) {
+        if rng.gen_bool(AUTOTROPH_CHANCE) {
+            return (Self::choose_autotrophy(habitat, rng), TrophicRole::Producer);
+        }
+        let roles = [
+            (TrophicRole::Grazer, 35),
+            (TrophicRole::Predator, 25),
+            (TrophicRole::Decomposer, 25),
+            (TrophicRole::Parasite, 15),
+        ];
+        let role = roles.choose_weighted(rng, |role| role.1).unwrap().0.clone();
+        (EnergySource::Heterotroph, role)
+    }
+
+    fn choose_reproduction (size: &Size, organization: &Organization, rng: &mut impl Rng) -> Reproduction {
+        let modes = match (size, organization) {
+            (Size::SingleCell, _) => [
+                (Reproduction::Fission, 60), (Reproduction::Budding, 30), (Reproduction::Spores, 10),
+            ],
+            (Size::MultiCellular, Organization::Modular) => [
+                (Reproduction::Budding, 40), (Reproduction::Spores, 30), (Reproduction::Sexual, 30),
+            ],
+            (Size::MultiCellular, Organization::Unitary) => [
+                (Reproduction::Sexual, 80), (Reproduction::Budding, 15), (Reproduction::Spores, 5),
+            ],
+        };
+        modes.choose_weighted(rng, |mode| mode.1).unwrap().0.clone()
+    }
+
+    fn choose_locomotion (size: &Size, organization: &Organization, habitat: &Habitat,
+                          trophic_role: &TrophicRole, rng: &mut impl Rng) -> Locomotion {
+        match (size, organization) {
+            (Size::SingleCell, _) => {
+                if rng.gen_bool(0.5) { Locomotion::Flagella } else { Locomotion::Drifting }
+            }
+            (Size::MultiCellular, Organization::Modular) => match habitat {
+                Habitat::Pelagic | Habitat::Aerial => Locomotion::Drifting,
+                _ => Locomotion::Sessile,
+            },
+            (Size::MultiCellular, Organization::Unitary) => match habitat {
+                Habitat::Pelagic => Locomotion::Swimming,
+                Habitat::Aerial => Locomotion::Flying,
+                Habitat::Subsurface => Locomotion::Burrowing,
+                _ if *trophic_role == TrophicRole::Producer => Locomotion::Sessile,
+                Habitat::Terrestrial if rng.gen_bool(0.5) => Locomotion::Walking,
+                _ => Locomotion::Crawling,
+            },
+        }
+    }
+
+    fn choose_senses (habitat: &Habitat, locomotion: &Locomotion, energy_source: &EnergySource,
+                      rng: &mut impl Rng) -> Vec<Sense> {
+        let mut senses = vec![Sense::Chemoreception];
+        if *habitat != Habitat::Subsurface
+            && (*energy_source == EnergySource::Phototroph || rng.gen_bool(PHOTORECEPTION_CHANCE)) {
+            senses.push(Sense::Photoreception);
+        }
+        if *locomotion != Locomotion::Sessile && *locomotion != Locomotion::Drifting {
+            senses.push(Sense::Mechanoreception);
+        }
+        if (*habitat == Habitat::Pelagic || *habitat == Habitat::Benthic)
+            && rng.gen_bool(ELECTRORECEPTION_CHANCE) {
+            senses.push(Sense::Electroreception);
+        }
+        // Chemotrophs home in on the heat of the vents and seeps that feed them
+        if *energy_source == EnergySource::Chemotroph {
+            senses.push(Sense::Thermoreception);
+        }
+        senses
+    }
+
+    // Tool use takes a large, mobile, oxygen-breathing animal that forages by sight and has limbs
+    // to spare for handling things
+    pub fn can_use_tools(&self) -> bool {
+        let can_grasp = self.body_plan.get_appendages().iter().any(|(appendage, count)| match appendage {
+            Appendage::Claws | Appendage::Tentacles => true,
+            Appendage::Legs => *count >= MINIMUM_LEGS_FOR_HANDS,
+            _ => false,
+        });
+        self.size == Size::MultiCellular
+            && self.organization == Organization::Unitary
+            && self.symmetry == Symmetry::Bilateral
+            && self.locomotion != Locomotion::Sessile
+            && self.metabolism == Metabolism::Aerobic
+            && self.energy_source == EnergySource::Heterotroph
+            && self.senses.contains(&Sense::Photoreception)
+            && can_grasp
+    }
+
     pub fn get_info(&self) -> String{
-        format!("This organism is barcoded: {}. It is {} and {}, has {} symmetry, is made of {}, uses {} for a solvent, \
-        and is {}.", &self.name, &self.size, &self.organization,
+        let senses: Vec<String> = self.senses.iter().map(|sense| sense.to_string()).collect();
+        format!("This organism is {}. It is {} and {}, has {} symmetry, is made of {}, uses {} for a solvent, \
+        and is {}. It is a {} {} with a {} habitat, reproduces by {}, gets around by {}, \
+        and senses its surroundings through {}. {}", &self.name, &self.size, &self.organization,
                 &self.symmetry, &self.structure, &self.solvent,
-                &self.metabolism)
+                &self.metabolism, &self.energy_source, &self.trophic_role, &self.habitat,
+                &self.reproduction, &self.locomotion, senses.join(", "),
+                self.body_plan.get_description(&self.symmetry))
     }
 
     pub fn get_name(&self) -> &String {
         &self.name
     }
 
+    pub fn get_genus(&self) -> &String {
+        &self.genus
+    }
+
     pub fn get_size(&self) -> &Size {
         &self.size
     }
@@ -114,4 +486,32 @@ impl Organism {
     pub fn get_metabolism(&self) -> &Metabolism {
         &self.metabolism
     }
+
+    pub fn get_energy_source(&self) -> &EnergySource {
+        &self.energy_source
+    }
+
+    pub fn get_trophic_role(&self) -> &TrophicRole {
+        &self.trophic_role
+    }
+
+    pub fn get_reproduction(&self) -> &Reproduction {
+        &self.reproduction
+    }
+
+    pub fn get_habitat(&self) -> &Habitat {
+        &self.habitat
+    }
+
+    pub fn get_locomotion(&self) -> &Locomotion {
+        &self.locomotion
+    }
+
+    pub fn get_senses(&self) -> &Vec<Sense> {
+        &self.senses
+    }
+
+    pub fn get_body_plan(&self) -> &BodyPlan {
+        &self.body_plan
+    }
 }
\ No newline at end of file
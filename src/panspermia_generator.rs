@@ -0,0 +1,208 @@
+use std::cell::{Cell, OnceCell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use rand::prelude::*;
+use strum_macros::Display;
+
+use crate::galaxy_generator::GalaxySettings;
+use crate::organism_generator::{Organism, Size};
+use crate::planet_generator;
+use crate::planetary_environment_generator::PlanetaryEnvironment;
+use crate::planetary_system_generator::PlanetarySystem;
+use crate::star_generator;
+
+const EJECTA_TRANSFER_RATE: f64 = 0.001; // chance per million years that a living world seeds a neighbour
+const INTERSTELLAR_TRANSFER_RATE: f64 = 0.0002; // at zero distance, falling to nothing at the range
+const INTERSTELLAR_RANGE: f64 = 50.0; // in light years
+const TRANSFER_SEED_MULTIPLIER: u64 = 1_000_003;
+const TRANSFER_SEED_OFFSET: u64 = 67867967; // keeps transfers independent of other rolls
+const NATIVE_PLANET_CACHE_CAPACITY: usize = 4096; // stars whose planets are kept before the cache is emptied
+
+#[derive(Display, Clone, PartialEq)]
+pub enum Route {
+    Ejecta, // rock blasted off one planet by an impact lands on another in the same system
+    Interstellar, // rock escapes its star altogether and drifts to another
+}
+
+// Life that reached a planet from another world, carried by a hardy ancestor from the source
+// planet's biosphere
+#[derive(Clone)]
+pub struct Seeding {
+    route: Route,
+    source: String,
+    time: u16, // the star's age in millions of years when life arrived
+    ancestor: Organism,
+}
+
+impl Seeding {
+    pub fn get_route(&self) -> &Route {
+        &self.route
+    }
+
+    pub fn get_source(&self) -> &String {
+        &self.source
+    }
+
+    pub fn get_time(&self) -> u16 {
+        self.time
+    }
+
+    pub fn get_ancestor(&self) -> &Organism {
+        &self.ancestor
+    }
+}
+
+type Neighbour = (u64, (f64, f64)); // a star's index in the galaxy and its position
+
+// Which stars lie close enough to seed one another, and the planets each would have on its own.
+// Both are slow to work out and asked for again for every habitable planet, so they are kept for
+// the whole galaxy.
+#[derive(Default)]
+pub struct Neighbourhood {
+    cells: OnceCell<HashMap<(i64, i64), Vec<Neighbour>>>, // in squares INTERSTELLAR_RANGE a side
+    native_planets: RefCell<HashMap<u64, Rc<Vec<planet_generator::Planet>>>>, // by star index
+    elapsed_time: Cell<u16>, // when the cached planets are from
+}
+
+impl Neighbourhood {
+    // Every star that could lie within range of the position: those in the same square or one
+    // next to it. They come in galaxy order, so ties between sources break the same way as ever.
+    fn get_neighbours(&self, galaxy: &GalaxySettings, position: (f64, f64)) -> Vec<Neighbour> {
+        let cells = self.cells.get_or_init(|| {
+            let mut cells: HashMap<(i64, i64), Vec<Neighbour>> = HashMap::new();
+            for index in 0..galaxy.get_size() {
+                let position = galaxy.create_star(index).get_position();
+                cells.entry(Self::get_cell(position)).or_default().push((index, position));
+            }
+            cells
+        });
+        let (x, y) = Self::get_cell(position);
+        let mut neighbours: Vec<Neighbour> = (x - 1..=x + 1)
+            .flat_map(|column| (y - 1..=y + 1).map(move |row| (column, row)))
+            .filter_map(|cell| cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        neighbours.sort_by_key(|neighbour| neighbour.0);
+        neighbours
+    }
+
+    fn get_cell(position: (f64, f64)) -> (i64, i64) {
+        ((position.0 / INTERSTELLAR_RANGE).floor() as i64, (position.1 / INTERSTELLAR_RANGE).floor() as i64)
+    }
+
+    // The planets of the star at the given index as they would be in isolation, at the given time
+    fn get_native_planets(&self, galaxy: &GalaxySettings, index: u64,
+                          elapsed_time: u16) -> Rc<Vec<planet_generator::Planet>> {
+        let mut native_planets = self.native_planets.borrow_mut();
+        if self.elapsed_time.get() != elapsed_time || native_planets.len() >= NATIVE_PLANET_CACHE_CAPACITY {
+            native_planets.clear();
+            self.elapsed_time.set(elapsed_time);
+        }
+        native_planets.entry(index)
+            .or_insert_with(|| {
+                let mut star = galaxy.create_star(index);
+                star.advance(elapsed_time);
+                Rc::new(PlanetarySystem::generate_native_planets(&star))
+            })
+            .clone()
+    }
+}
+
+// Builds a planet and lets life from elsewhere reach it if that happens before, or instead of,
+// life arising on its own
+pub fn generate_planet(star: &star_generator::Star, distance: u8, galaxy: &GalaxySettings,
+                       elapsed_time: u16) -> planet_generator::Planet {
+    let mut planet = planet_generator::Planet::new(star, distance);
+    if let Some(seeding) = find_seeding(star, &planet, galaxy, elapsed_time) {
+        planet.receive_life(seeding);
+    }
+    planet
+}
+
+// The planets of the star at the given index as life travelling between stars has left them.
+// Only habitable planets can be seeded, so the native planets are taken from the cache and just
+// those are looked at again.
+pub fn generate_planets(galaxy: &GalaxySettings, index: u64, elapsed_time: u16) -> Vec<planet_generator::Planet> {
+    let mut star = galaxy.create_star(index);
+    star.advance(elapsed_time);
+    galaxy.get_neighbourhood().get_native_planets(galaxy, index, elapsed_time).iter()
+        .map(|planet| {
+            let mut planet = planet.clone();
+            if let Some(seeding) = find_seeding(&star, &planet, galaxy, elapsed_time) {
+                planet.receive_life(seeding);
+            }
+            planet
+        })
+        .collect()
+}
+
+// Only planets where life arose on its own act as sources, so seeding never chains from world
+// to world. Each source's rolls are seeded from both planets, so they stay the same as time passes.
+pub fn find_seeding(star: &star_generator::Star, planet: &planet_generator::Planet, galaxy: &GalaxySettings,
+                elapsed_time: u16) -> Option<Seeding> {
+    if !planet.get_habitability() {
+        return None;
+    }
+    let habitable_since = planet.get_age() - planet.get_habitable_duration();
+    let deadline = planet.get_origin_of_life().unwrap_or(planet.get_age());
+
+    let neighbourhood = galaxy.get_neighbourhood();
+    let position = star.get_position();
+    let mut sources = Vec::new();
+    for (index, source_position) in neighbourhood.get_neighbours(galaxy, position) {
+        let (route, rate) = if galaxy.get_star_seed(index) == star.get_seed() {
+            (Route::Ejecta, EJECTA_TRANSFER_RATE)
+        } else {
+            let distance = ((position.0 - source_position.0).powi(2) + (position.1 - source_position.1).powi(2)).sqrt();
+            if distance > INTERSTELLAR_RANGE {
+                continue;
+            }
+            (Route::Interstellar, INTERSTELLAR_TRANSFER_RATE * (1.0 - distance / INTERSTELLAR_RANGE))
+        };
+        for source in neighbourhood.get_native_planets(galaxy, index, elapsed_time).iter() {
+            if source.get_seed() != planet.get_seed() && source.get_ocean_at(0) == planet.get_ocean() {
+                sources.push((source.clone(), route.clone(), rate));
+            }
+        }
+    }
+
+    let mut arrivals: Vec<(planet_generator::Planet, Route, u16, StdRng)> = sources.into_iter()
+        .filter_map(|(source, route, rate)| {
+            let origin = source.get_origin_of_life()?;
+            let mut rng = StdRng::seed_from_u64(planet.get_seed().wrapping_add(TRANSFER_SEED_OFFSET)
+                .wrapping_mul(TRANSFER_SEED_MULTIPLIER).wrapping_add(source.get_seed()));
+            let wait = -rng.gen_range(f64::EPSILON..1.0_f64).ln() / rate;
+            let arrival = origin.max(habitable_since) as f64 + wait;
+            if arrival >= deadline as f64 {
+                return None;
+            }
+            Some((source, route, arrival as u16, rng))
+        })
+        .collect();
+    arrivals.sort_by_key(|arrival| arrival.2);
+
+    // Building a source's biosphere is slow, so only the earliest arrivals are looked at
+    arrivals.into_iter().find_map(|(source, route, time, mut rng)| {
+        let ancestor = choose_ancestor(&source, time, &mut rng)?;
+        Some(Seeding { route, source: source.get_name(), time, ancestor })
+    })
+}
+
+// Single cells are the likeliest to survive the journey, so the traveller is one of those if any
+// were living on the source planet when the rock left
+fn choose_ancestor(source: &planet_generator::Planet, time: u16, rng: &mut impl Rng) -> Option<Organism> {
+    let phylogeny = PlanetaryEnvironment::generate_phylogeny(source);
+    let living: Vec<&Organism> = (0..phylogeny.get_lineage_count())
+        .filter_map(|index| phylogeny.get_lineage(index))
+        .filter(|lineage| lineage.get_origin() <= time && lineage.get_extinction().is_none_or(|extinction| extinction > time))
+        .map(|lineage| lineage.get_organism())
+        .collect();
+    let microbes: Vec<&Organism> = living.iter()
+        .filter(|organism| *organism.get_size() == Size::SingleCell)
+        .copied()
+        .collect();
+    let travellers = if microbes.is_empty() { living } else { microbes };
+    travellers.choose(rng).map(|&organism| organism.clone())
+}
@@ -0,0 +1,228 @@
+use crate::organism_generator::Organism;
+
+// A species and where it sits in the tree of life. Times are the star's age in millions of years.
+pub struct Lineage {
+    organism: Organism,
+    parent: Option<usize>,
+    origin: u16,
+    extinction: Option<u16>,
+}
+
+impl Lineage {
+    pub fn get_organism(&self) -> &Organism {
+        &self.organism
+    }
+
+    pub fn get_parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    pub fn get_origin(&self) -> u16 {
+        self.origin
+    }
+
+    pub fn get_extinction(&self) -> Option<u16> {
+        self.extinction
+    }
+
+    pub fn is_living(&self) -> bool {
+        self.extinction.is_none()
+    }
+}
+
+// Every species that has ever lived on a planet, descended from a last universal common ancestor.
+// Species bud off from a parent lineage, which carries on alongside its descendants.
+pub struct Phylogeny {
+    lineages: Vec<Lineage>,
+    present: u16,
+}
+
+impl Phylogeny {
+    pub fn new(present: u16) -> Phylogeny {
+        Phylogeny { lineages: Vec::new(), present }
+    }
+
+    pub fn add_common_ancestor(&mut self, organism: Organism, time: u16) -> usize {
+        self.lineages.push(Lineage { organism, parent: None, origin: time, extinction: None });
+        self.lineages.len() - 1
+    }
+
+    pub fn branch(&mut self, parent: usize, organism: Organism, time: u16) -> usize {
+        self.lineages.push(Lineage { organism, parent: Some(parent), origin: time, extinction: None });
+        self.lineages.len() - 1
+    }
+
+    pub fn go_extinct(&mut self, index: usize, time: u16) {
+        self.lineages[index].extinction = Some(time);
+    }
+
+    pub fn get_lineage(&self, index: usize) -> Option<&Lineage> {
+        self.lineages.get(index)
+    }
+
+    pub fn get_present(&self) -> u16 {
+        self.present
+    }
+
+    pub fn get_lineage_count(&self) -> usize {
+        self.lineages.len()
+    }
+
+    pub fn get_living(&self) -> Vec<usize> {
+        (0..self.lineages.len()).filter(|&i| self.lineages[i].is_living()).collect()
+    }
+
+    pub fn get_children(&self, index: usize) -> Vec<usize> {
+        (0..self.lineages.len()).filter(|&i| self.lineages[i].parent == Some(index)).collect()
+    }
+
+    // The lineage and all of its descendants
+    pub fn get_clade(&self, index: usize) -> Vec<usize> {
+        let mut clade = vec![index];
+        let mut i = 0;
+        while i < clade.len() {
+            clade.extend(self.get_children(clade[i]));
+            i += 1;
+        }
+        clade
+    }
+
+    // From the common ancestor down to the lineage itself
+    pub fn get_ancestry(&self, index: usize) -> Vec<usize> {
+        let mut ancestry = vec![index];
+        while let Some(parent) = self.lineages[*ancestry.last().unwrap()].parent {
+            ancestry.push(parent);
+        }
+        ancestry.reverse();
+        ancestry
+    }
+
+    pub fn get_clade_info(&self, index: usize) -> String {
+        if index >= self.lineages.len() {
+            return "Invalid coordinates.".to_string();
+        }
+        let lineage = &self.lineages[index];
+
+        let ancestry: Vec<&str> = self.get_ancestry(index).iter()
+            .map(|&i| self.lineages[i].organism.get_name().as_str())
+            .collect();
+        let clade = self.get_clade(index);
+        let living = clade.iter().filter(|&&i| self.lineages[i].is_living()).count();
+        let status = match lineage.extinction {
+            Some(extinction) => format!("went extinct {} million years ago", self.present - extinction),
+            None => "is still living".to_string(),
+        };
+
+        format!("{} appeared {} million years ago and {}. Its ancestry is {}. \
+        Its clade holds {} species, {} of which are still living.",
+                lineage.organism.get_name(), self.present - lineage.origin, status,
+                ancestry.join(" > "), clade.len(), living)
+    }
+
+    // Indented outline of the whole tree, numbered so that clades can be looked up
+    pub fn get_tree_info(&self) -> String {
+        let mut s = String::new();
+        for root in 0..self.lineages.len() {
+            if self.lineages[root].parent.is_none() {
+                self.write_tree_info(root, 0, &mut s);
+            }
+        }
+        s
+    }
+
+    fn write_tree_info(&self, index: usize, depth: usize, s: &mut String) {
+        let lineage = &self.lineages[index];
+        s.push_str(&"  ".repeat(depth));
+        s.push_str(&format!("{}: {}", index, lineage.organism.get_name()));
+        if !lineage.is_living() {
+            s.push_str(" (extinct)");
+        }
+        s.push('\n');
+        for child in self.get_children(index) {
+            self.write_tree_info(child, depth + 1, s);
+        }
+    }
+
+    // Branch lengths are in millions of years. Each budding event splits the parent lineage in two,
+    // so the result is a fully bifurcating tree whose leaves are the living and extinct species.
+    pub fn to_newick(&self) -> String {
+        let mut trees = Vec::new();
+        for root in 0..self.lineages.len() {
+            if self.lineages[root].parent.is_none() {
+                trees.push(format!("{};", self.write_newick(root, self.lineages[root].origin, None)));
+            }
+        }
+        trees.join("\n")
+    }
+
+    // Writes the lineage from the given time onward, after it has budded off every child up to
+    // and including `after`
+    fn write_newick(&self, index: usize, start: u16, after: Option<(u16, usize)>) -> String {
+        let lineage = &self.lineages[index];
+        let end = lineage.extinction.unwrap_or(self.present);
+
+        let next_branch = self.get_children(index).into_iter()
+            .map(|child| (self.lineages[child].origin, child))
+            .filter(|&branch| after.is_none_or(|after| branch > after))
+            .min();
+
+        match next_branch {
+            Some((time, child)) => format!(
+                "({},{}):{}",
+                self.write_newick(index, time, Some((time, child))),
+                self.write_newick(child, time, None),
+                time - start,
+            ),
+            // Newick labels can't contain spaces
+            None => format!("{}:{}", lineage.organism.get_name().replace(' ', "_"), end - start),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::galaxy_generator::GalaxyShape;
+    use crate::planet_generator::Planet;
+    use crate::star_generator::Star;
+
+    // Species from the first planet in the galaxy where life has begun
+    fn organisms(count: u64) -> Vec<Organism> {
+        let (planet, origin) = (0..)
+            .flat_map(|seed| {
+                let star = Star::new(seed, &GalaxyShape::Disc);
+                (1..10).map(move |distance| Planet::new(&star, distance))
+            })
+            .find_map(|planet| planet.get_origin_of_life().map(|origin| (planet, origin)))
+            .unwrap();
+        (0..count).map(|seed| Organism::new(&planet, origin, seed)).collect()
+    }
+
+    fn label(organism: &Organism) -> String {
+        organism.get_name().replace(' ', "_")
+    }
+
+    #[test]
+    fn a_lone_ancestor_is_a_single_leaf() {
+        let organisms = organisms(1);
+        let mut phylogeny = Phylogeny::new(500);
+        phylogeny.add_common_ancestor(organisms[0].clone(), 100);
+        assert_eq!(phylogeny.to_newick(), format!("{}:400;", label(&organisms[0])));
+    }
+
+    #[test]
+    fn each_budding_splits_the_parent_lineage() {
+        let organisms = organisms(3);
+        let mut phylogeny = Phylogeny::new(500);
+        let ancestor = phylogeny.add_common_ancestor(organisms[0].clone(), 100);
+        let first = phylogeny.branch(ancestor, organisms[1].clone(), 200);
+        phylogeny.branch(ancestor, organisms[2].clone(), 300);
+        phylogeny.go_extinct(first, 400);
+
+        let (ancestor, first, second) = (label(&organisms[0]), label(&organisms[1]), label(&organisms[2]));
+        assert_eq!(
+            phylogeny.to_newick(),
+            format!("(({}:200,{}:200):100,{}:200):100;", ancestor, second, first),
+        );
+    }
+}
@@ -1,7 +1,10 @@
 use rand::prelude::*;
 use strum_macros::Display;
-use strum_macros::EnumIter;
 
+use crate::catastrophe_generator::{Catastrophe, CatastropheKind};
+use crate::name_generator;
+use crate::panspermia_generator::{Route, Seeding};
+use crate::planetary_environment_generator::BIOSPHERE_TIME_STEP;
 use crate::star_generator;
 
 const BACKGROUND_TEMPERATURE: u16 = 3; // in kelvins; prevents absolute zero worlds
@@ -9,11 +12,29 @@ const PRESSURE_AT_WHICH_INSULATION_OCCURS: u32 = 10000;
 const ATMOSPHERIC_INSULATION: u16 = 150; // in kelvins
 const MAX_TEMP_FOR_ATMOSPHERE_ON_SMALL_WORLDS: u16 = 100; // in kelvins
 const DISTANCE_FROM_STAR_MODIFIER: u16 = 4; // for calibrating how much temperature drops with distance from a star
-const MINIMUM_STAR_AGE_FOR_LIFE: u16 = 1000; // in millions of years
+const HABITABLE_DURATION_TIME_STEP: u16 = 10; // in millions of years
 const MINIMUM_MASS_FOR_MAGNETOSPHERE: u32 = 5000; // mass currently has no units and is arbitrary
 const MINIMUM_MASS_FOR_ATMOSPHERE: u32 = 1000;
+const DYNAMO_LIFETIME_PER_MASS: u32 = 2; // in millions of years; larger cores take longer to freeze
+const ATMOSPHERIC_ESCAPE_HALF_LIFE: u16 = 2000; // in millions of years without a magnetic field
+const PLANET_SEED_MULTIPLIER: u64 = 256; // keeps every planet's seed unique within the galaxy
+const ABIOGENESIS_SEED_OFFSET: u64 = 7919; // keeps the origin of life independent of other rolls
+const ABIOGENESIS_RATE: f64 = 0.0005; // chance per million years under ideal conditions
+const AMMONIA_ABIOGENESIS_FACTOR: f64 = 0.5; // ammonia dissolves fewer prebiotic molecules than water
+const MASS_FOR_FULL_GEOTHERMAL_ENERGY: u32 = 10000; // hydrothermal vents supply energy alongside starlight
+const THIN_ATMOSPHERE_ABIOGENESIS_FACTOR: f64 = 0.5; // volatiles escape before they can concentrate
+const MINIMUM_CHEMISTRY_FACTOR: f64 = 0.1;
+const SURFACE_SEED_OFFSET: u64 = 104729; // keeps the lay of the land independent of other rolls
+const MINIMUM_OCEAN_COVERAGE: f64 = 0.05;
+const MAXIMUM_AXIAL_TILT: u8 = 90; // in degrees
+const OXYGENATION_TIME: u16 = 1500; // in millions of years for photosynthesis to oxygenate the air
+const NAME_SEED_OFFSET: u64 = 15485863; // keeps naming independent of the planet's physical rolls
+const PROPER_NAME_CHANCE: f64 = 0.2;
+const ONE_GRAVITY_MASS: f64 = 5000.0; // a rocky world of this mass pulls as hard as the Earth
+const RUNAWAY_GREENHOUSE_TEMPERATURE: u16 = 730; // in kelvins, like Venus
+const RUNAWAY_GREENHOUSE_PRESSURE: u32 = 1_000_000; // thick with the boiled-off ocean
 
-#[derive(Display)]
+#[derive(Display, Clone)]
 pub enum PlanetClass {
     Rocky,
     GasGiant,
@@ -21,47 +42,128 @@ pub enum PlanetClass {
     Dwarf,
 }
 
-#[derive(PartialEq)]
+#[derive(Display, Clone, PartialEq)]
 pub enum Ocean {
     Water,
     Ammonia,
     None,
 }
 
+#[derive(Clone)]
 pub struct Planet {
+    seed: u64,
+    designation: String,
+    proper_name: Option<String>,
+    age: u16,
     class: PlanetClass,
     distance_from_star: u8,
     mass: u32,
+    star_mass: f64, // in solar masses; fixed over the star's lifetime
+    star: star_generator::Star, // as it was when the planet was generated, for looking back at its history
     magnetic_field: bool,
+    primordial_pressure: u32, // before any of the atmosphere escaped
     pressure: u32,
     temperature: u16,
     ocean: Ocean,
+    habitable_duration: u16,
     habitable: bool,
+    abiogenesis_probability: f64,
+    origin_of_life: Option<u16>,
+    seeding: Option<Seeding>, // set when life came from another world rather than arising here
+    ocean_coverage: f64,
+    axial_tilt: u8,
+    runaway_greenhouse: Option<u16>, // the star's age when the ocean boiled away, if it has
 }
 
 impl Planet {
     pub fn new(star: &star_generator::Star, distance: u8) -> Planet {
 
-        let seed: u64 = star.get_age() as u64 * distance as u64;
+        let seed: u64 = star.get_seed().wrapping_mul(PLANET_SEED_MULTIPLIER).wrapping_add(distance as u64);
         let mut rng = StdRng::seed_from_u64(seed);
 
         let size = Self::calculate_mass_and_class(&mut rng, distance);
-        let atmosphere = Self::calculate_temperature_and_pressure(distance, &star, &mut rng, size.0);
-        let magnetic_field = if size.0 >= MINIMUM_MASS_FOR_MAGNETOSPHERE { true } else { false };
+        let magnetic_field = Self::calculate_magnetic_field(size.0, star.get_age());
+        let atmosphere = Self::calculate_temperature_and_pressure(distance, star, &mut rng, size.0);
         let ocean = Self::thalassogenesis(atmosphere.0, atmosphere.1);
-        let habitable = if ocean != Ocean::None
-            && magnetic_field && star.get_age() > MINIMUM_STAR_AGE_FOR_LIFE { true } else { false };
+        let habitable_duration = Self::calculate_habitable_duration(distance, star, atmosphere.1);
+        let habitable = ocean != Ocean::None && magnetic_field;
+        let abiogenesis_rate = if habitable {
+            Self::calculate_abiogenesis_rate(&ocean, atmosphere.0, atmosphere.1, size.0)
+        } else {
+            0.0
+        };
+        let abiogenesis_probability = 1.0 - (-abiogenesis_rate * habitable_duration as f64).exp();
+        let origin_of_life = Self::calculate_origin_of_life(
+            seed, abiogenesis_rate, star.get_age() - habitable_duration, star.get_age(),
+        );
+        let surface = Self::calculate_ocean_coverage_and_tilt(seed, &ocean);
+
+        let mut name_rng = StdRng::seed_from_u64(seed.wrapping_add(NAME_SEED_OFFSET));
+        let proper_name = if name_rng.gen_bool(PROPER_NAME_CHANCE) {
+            Some(name_generator::generate_proper_name(name_rng.gen()))
+        } else {
+            None
+        };
 
-        Planet {
+        let mut planet = Planet {
+            seed,
+            designation: name_generator::generate_planet_designation(star.get_designation(), distance),
+            proper_name,
+            age: star.get_age(),
             class: size.1,
             distance_from_star: distance,
             mass: size.0,
+            star_mass: star.get_mass(),
+            star: star.clone(),
             magnetic_field,
+            primordial_pressure: atmosphere.2,
             pressure: atmosphere.1,
             temperature: atmosphere.0,
             ocean,
+            habitable_duration,
             habitable,
+            abiogenesis_probability,
+            origin_of_life,
+            seeding: None,
+            ocean_coverage: surface.0,
+            axial_tilt: surface.1,
+            runaway_greenhouse: None,
+        };
+
+        // Life that would only have arisen once the ocean was gone never does. Life that arose in
+        // the step before is struck along with everything else.
+        planet.runaway_greenhouse = planet.find_runaway_greenhouse();
+        if let Some(time) = planet.runaway_greenhouse {
+            if planet.origin_of_life.is_some_and(|origin| origin >= time) {
+                planet.origin_of_life = None;
+            }
+        }
+        planet
+    }
+
+    // Looks through the planet's history since its ocean formed for the first runaway greenhouse,
+    // rolling the same events its biosphere does, step by step
+    fn find_runaway_greenhouse(&self) -> Option<u16> {
+        if self.ocean == Ocean::None {
+            return None;
         }
+        let ocean_formed = self.age - self.habitable_duration;
+        (ocean_formed.div_ceil(BIOSPHERE_TIME_STEP)..self.age / BIOSPHERE_TIME_STEP)
+            .find(|&step| Catastrophe::roll_step(self, step).0.iter()
+                .any(|(kind, _)| *kind == CatastropheKind::RunawayGreenhouse))
+            .map(|step| step * BIOSPHERE_TIME_STEP)
+    }
+
+    // Whether the ocean had already boiled away at the given age of the star
+    fn is_boiled_at(&self, age: u16) -> bool {
+        self.runaway_greenhouse.is_some_and(|time| age > time)
+    }
+
+    fn calculate_ocean_coverage_and_tilt (seed: u64, ocean: &Ocean) -> (f64, u8) {
+        let mut rng = StdRng::seed_from_u64(seed.wrapping_add(SURFACE_SEED_OFFSET));
+        let tilt = rng.gen_range(0..=MAXIMUM_AXIAL_TILT);
+        let coverage = if *ocean == Ocean::None { 0.0 } else { rng.gen_range(MINIMUM_OCEAN_COVERAGE..=1.0) };
+        (coverage, tilt)
     }
 
     fn calculate_mass_and_class (rng: &mut impl Rng, distance: u8) -> (u32, PlanetClass) {
@@ -87,31 +189,174 @@ impl Planet {
         }
     }
 
-    fn calculate_temperature_and_pressure (distance: u8, star: &star_generator::Star, rng: &mut impl Rng, mass: u32) -> (u16, u32){
+    // Planets lose their magnetic field once their cores freeze
+    fn calculate_magnetic_field (mass: u32, age: u16) -> bool {
+        mass >= MINIMUM_MASS_FOR_MAGNETOSPHERE && (age as u32) < mass * DYNAMO_LIFETIME_PER_MASS
+    }
+
+    // The present temperature and pressure, and the pressure the planet started out with
+    fn calculate_temperature_and_pressure (distance: u8, star: &star_generator::Star, rng: &mut impl Rng, mass: u32) -> (u16, u32, u32){
         // TODO: pressure needs to scale with mass; small planets can't have high pressures;
         // large planets can't have low pressures
-        let mut temperature: u16 = &star.get_temperature() / (distance as u16 * distance as u16 * DISTANCE_FROM_STAR_MODIFIER)
-            + BACKGROUND_TEMPERATURE;
+        let temperature = Self::calculate_temperature_at(distance, star, 0, star.get_age());
         let mut pressure: u32 = 0;
         if mass > MINIMUM_MASS_FOR_ATMOSPHERE || temperature < MAX_TEMP_FOR_ATMOSPHERE_ON_SMALL_WORLDS {
             pressure = rng.gen_range(1..10) * 10_u32.pow(rng.gen_range(0..8));
         }
+        let primordial_pressure = pressure;
+        pressure = Self::calculate_atmospheric_escape(pressure, mass, star.get_age());
+        (Self::calculate_temperature_at(distance, star, pressure, star.get_age()), pressure, primordial_pressure)
+    }
+
+    // Without a magnetic field, stellar wind strips the atmosphere away
+    fn calculate_atmospheric_escape (pressure: u32, mass: u32, age: u16) -> u32 {
+        let unprotected_time = if mass >= MINIMUM_MASS_FOR_MAGNETOSPHERE {
+            (age as u32).saturating_sub(mass * DYNAMO_LIFETIME_PER_MASS)
+        } else {
+            age as u32
+        };
+        pressure
+            .checked_shr(unprotected_time / ATMOSPHERIC_ESCAPE_HALF_LIFE as u32)
+            .unwrap_or(0)
+    }
+
+    fn calculate_temperature_at (distance: u8, star: &star_generator::Star, pressure: u32, age: u16) -> u16 {
+        let mut temperature = (star.get_heating_temperature_at(age)
+            / (distance as f64 * distance as f64 * DISTANCE_FROM_STAR_MODIFIER as f64)) as u16
+            + BACKGROUND_TEMPERATURE;
         if pressure > PRESSURE_AT_WHICH_INSULATION_OCCURS { temperature += ATMOSPHERIC_INSULATION };
-        (temperature, pressure)
+        temperature
+    }
+
+    // How long, up to the star's present age, the planet has continuously held a liquid ocean
+    // as the star's habitable zone migrated past it
+    fn calculate_habitable_duration (distance: u8, star: &star_generator::Star, pressure: u32) -> u16 {
+        let mut duration: u16 = 0;
+        let mut age = star.get_age();
+        loop {
+            let temperature = Self::calculate_temperature_at(distance, star, pressure, age);
+            if Self::thalassogenesis(temperature, pressure) == Ocean::None {
+                break;
+            }
+            if age < HABITABLE_DURATION_TIME_STEP {
+                duration += age;
+                break;
+            }
+            duration += HABITABLE_DURATION_TIME_STEP;
+            age -= HABITABLE_DURATION_TIME_STEP;
+        }
+        duration
+    }
+
+    // How likely life is to emerge in each million years, from the solvent, the energy available
+    // to drive prebiotic chemistry, and how favourable conditions are for that chemistry
+    fn calculate_abiogenesis_rate (ocean: &Ocean, temperature: u16, pressure: u32, mass: u32) -> f64 {
+        let (solvent, freezing, boiling) = match ocean {
+            Ocean::Water => (1.0, 273.0, 373.0),
+            Ocean::Ammonia => (AMMONIA_ABIOGENESIS_FACTOR, 196.0, 240.0),
+            Ocean::None => return 0.0,
+        };
+
+        let geothermal = (mass as f64 / MASS_FOR_FULL_GEOTHERMAL_ENERGY as f64).min(1.0);
+        let energy = 0.5 + 0.5 * geothermal;
+
+        // Reactions run best away from the solvent's freezing and boiling points
+        let midpoint = (freezing + boiling) / 2.0;
+        let closeness = 1.0 - (temperature as f64 - midpoint).abs() / (boiling - midpoint);
+        let atmosphere = if pressure > PRESSURE_AT_WHICH_INSULATION_OCCURS { 1.0 }
+            else { THIN_ATMOSPHERE_ABIOGENESIS_FACTOR };
+        let chemistry = closeness.max(MINIMUM_CHEMISTRY_FACTOR) * atmosphere;
+
+        ABIOGENESIS_RATE * solvent * energy * chemistry
+    }
+
+    // Rolls once, from the planet's seed, how long after the ocean formed life appears. Because the
+    // roll never changes, a planet that has not yet come alive may still do so as time passes.
+    fn calculate_origin_of_life (seed: u64, rate: f64, ocean_formed: u16, age: u16) -> Option<u16> {
+        if rate <= 0.0 {
+            return None;
+        }
+        let mut rng = StdRng::seed_from_u64(seed.wrapping_add(ABIOGENESIS_SEED_OFFSET));
+        let roll: f64 = rng.gen();
+        let waiting_time = -(1.0 - roll).ln() / rate;
+        let origin = ocean_formed as f64 + waiting_time;
+        if origin <= age as f64 { Some(origin as u16) } else { None }
     }
 
     fn thalassogenesis (temperature: u16, pressure: u32) -> Ocean {
-        if temperature >= 273 && temperature <= 373 && pressure > 0 { Ocean::Water }
-        else if temperature >= 196 && temperature <= 240 && pressure > 0 { Ocean::Ammonia }
+        if (273..=373).contains(&temperature) && pressure > 0 { Ocean::Water }
+        else if (196..=240).contains(&temperature) && pressure > 0 { Ocean::Ammonia }
         else { Ocean::None }
     }
 
     pub fn get_info(&self) -> String{
-        format!("This is a {} planet, which is {} AU from its star, \
-        temperature: {} K, pressure: {}, mass: {}, magnetic field: {}, \
-         and it's {} that there is life here.", &self.class, &self.distance_from_star,
-                &self.temperature, &self.pressure, &self.mass,
-                &self.magnetic_field, &self.habitable)
+        format!("This is {}, a {} planet, which is {} AU from its star, \
+        temperature: {} K, pressure: {}, mass: {}, magnetic field: {}, axial tilt: {}°, \
+        ocean coverage: {:.0}%, it has held an ocean for {} million years, \
+        and there was a {:.0}% chance of life arising in that time. {}", self.get_name(), &self.class, &self.distance_from_star,
+                self.get_temperature(), self.get_pressure(), &self.mass,
+                &self.magnetic_field, &self.axial_tilt, self.get_ocean_coverage() * 100.0, self.get_habitable_duration(),
+                self.abiogenesis_probability * 100.0, self.get_origin_info())
+    }
+
+    // Life from another world takes the place of any that would have arisen here later
+    pub fn receive_life(&mut self, seeding: Seeding) {
+        self.origin_of_life = Some(seeding.get_time());
+        self.seeding = Some(seeding);
+    }
+
+    fn get_origin_info(&self) -> String {
+        let history = self.get_life_info();
+        match self.runaway_greenhouse {
+            Some(time) if self.origin_of_life.is_some() => format!(
+                "{} A runaway greenhouse boiled its ocean away {} million years ago, killing everything living here.",
+                history, self.age - time),
+            Some(time) => format!("A runaway greenhouse boiled its ocean away {} million years ago.", self.age - time),
+            None => history,
+        }
+    }
+
+    fn get_life_info(&self) -> String {
+        if let Some(seeding) = &self.seeding {
+            let route = match seeding.get_route() {
+                Route::Ejecta => "on rock blasted off",
+                Route::Interstellar => "on rock that drifted between the stars from",
+            };
+            let oxygen = if self.has_free_oxygen_at(self.age) { " It has since filled the air with oxygen." } else { "" };
+            return format!("Life arrived here {} million years ago, {} {}.{}",
+                           self.age - seeding.get_time(), route, seeding.get_source(), oxygen);
+        }
+        match self.origin_of_life {
+            Some(origin) if self.has_free_oxygen_at(self.age) => format!(
+                "Life arose here {} million years ago and has filled the air with oxygen.",
+                self.age - origin),
+            Some(origin) => format!("Life arose here {} million years ago.", self.age - origin),
+            None if self.get_habitability() => "It is habitable, but sterile.".to_string(),
+            None => "There is no life here.".to_string(),
+        }
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_designation(&self) -> &String {
+        &self.designation
+    }
+
+    pub fn get_proper_name(&self) -> Option<&String> {
+        self.proper_name.as_ref()
+    }
+
+    pub fn get_name(&self) -> String {
+        match &self.proper_name {
+            Some(proper_name) => format!("{} ({})", self.designation, proper_name),
+            None => self.designation.clone(),
+        }
+    }
+
+    pub fn get_age(&self) -> u16 {
+        self.age
     }
 
     pub fn get_class(&self) -> &PlanetClass {
@@ -126,24 +371,111 @@ impl Planet {
         self.mass
     }
 
+    // In multiples of the Earth's; at a fixed density, radius and surface gravity both grow with
+    // the cube root of mass
+    pub fn get_surface_gravity(&self) -> f64 {
+        (self.mass as f64 / ONE_GRAVITY_MASS).cbrt()
+    }
+
     pub fn get_magnetic_field(&self) -> bool {
         self.magnetic_field
     }
 
+    // Whether the planet was shielded at an earlier point in its history
+    pub fn has_magnetic_field_at(&self, age: u16) -> bool {
+        Self::calculate_magnetic_field(self.mass, age)
+    }
+
+    pub fn get_star_mass(&self) -> f64 {
+        self.star_mass
+    }
+
     pub fn get_pressure(&self) -> u32 {
-        self.pressure
+        self.get_pressure_at(self.age)
     }
 
     pub fn get_temperature(&self) -> u16 {
-        self.temperature
+        self.get_temperature_at(self.age)
     }
 
     pub fn get_ocean(&self) -> &Ocean {
-        &self.ocean
+        self.get_ocean_at(self.age)
+    }
+
+    // Conditions before a runaway greenhouse are the ones life evolved under
+    pub fn get_pressure_at(&self, age: u16) -> u32 {
+        if self.is_boiled_at(age) { self.pressure.max(RUNAWAY_GREENHOUSE_PRESSURE) } else { self.pressure }
+    }
+
+    pub fn get_temperature_at(&self, age: u16) -> u16 {
+        if self.is_boiled_at(age) { self.temperature.max(RUNAWAY_GREENHOUSE_TEMPERATURE) } else { self.temperature }
+    }
+
+    pub fn get_ocean_at(&self, age: u16) -> &Ocean {
+        if self.is_boiled_at(age) { &Ocean::None } else { &self.ocean }
+    }
+
+    // The temperature and ocean the planet really had at the given age of the star, as its star
+    // brightened and its air escaped. Unlike the conditions life evolved under, these don't change
+    // as the star ages further, so events rolled from them stay put.
+    pub fn get_climate_at(&self, age: u16) -> (u16, Ocean) {
+        if self.is_boiled_at(age) {
+            return (self.get_temperature_at(age), Ocean::None);
+        }
+        let pressure = Self::calculate_atmospheric_escape(self.primordial_pressure, self.mass, age);
+        let temperature = Self::calculate_temperature_at(self.distance_from_star, &self.star, pressure, age);
+        (temperature, Self::thalassogenesis(temperature, pressure))
+    }
+
+    pub fn get_ocean_coverage(&self) -> f64 {
+        if self.is_boiled_at(self.age) { 0.0 } else { self.ocean_coverage }
+    }
+
+    pub fn get_axial_tilt(&self) -> u8 {
+        self.axial_tilt
+    }
+
+    pub fn get_habitable_duration(&self) -> u16 {
+        if self.is_boiled_at(self.age) { 0 } else { self.habitable_duration }
     }
 
     pub fn get_habitability(&self) -> bool {
-        self.habitable
+        self.habitable && !self.is_boiled_at(self.age)
+    }
+
+    // The star's age, in millions of years, when a runaway greenhouse boiled the ocean away
+    pub fn get_runaway_greenhouse(&self) -> Option<u16> {
+        self.runaway_greenhouse
+    }
+
+    pub fn get_abiogenesis_probability(&self) -> f64 {
+        self.abiogenesis_probability
+    }
+
+    // The star's age, in millions of years, when life arose
+    pub fn get_origin_of_life(&self) -> Option<u16> {
+        self.origin_of_life
+    }
+
+    pub fn get_seeding(&self) -> Option<&Seeding> {
+        self.seeding.as_ref()
+    }
+
+    // Whether anything lives here at the given age of the star: life must have begun, and no
+    // runaway greenhouse have ended it
+    pub fn has_life_at(&self, age: u16) -> bool {
+        self.origin_of_life.is_some_and(|origin| origin <= age)
+            && self.runaway_greenhouse.is_none_or(|time| age < time)
+    }
+
+    pub fn has_life(&self) -> bool {
+        self.has_life_at(self.age)
+    }
+
+    // Oxygen only builds up once life has been splitting water for long enough
+    pub fn has_free_oxygen_at(&self, age: u16) -> bool {
+        *self.get_ocean_at(age) == Ocean::Water
+            && self.origin_of_life.is_some_and(|origin| age >= origin.saturating_add(OXYGENATION_TIME))
     }
 }
 
@@ -1,47 +1,231 @@
 use rand::prelude::*;
 
-use crate::game_manager::{Scene, SceneName};
+use crate::biosphere_metrics_generator;
+use crate::biosphere_metrics_generator::BiosphereMetrics;
+use crate::catastrophe_generator::{Catastrophe, CatastropheKind};
+use crate::civilization_generator::Civilization;
+use crate::food_web_generator::FoodWeb;
+use crate::panspermia_generator::Seeding;
+use crate::game_manager::{Level, Property, Scene, SceneName, Unit};
+use crate::moon_generator;
+use crate::moon_generator::{Moon, MoonEnvironment};
 use crate::planet_generator;
 use crate::organism_generator::Organism;
+use crate::phylogeny_generator::Phylogeny;
 use crate::planet_generator::PlanetClass;
+use crate::region_generator::{Region, RegionEnvironment};
 
 const RICHNESS_MINIMUM: u8 = 3;
 const RICHNESS_MAXIMUM: u8 = 12;
+const EVOLUTION_TIME_FOR_FULL_RICHNESS: u16 = 4000; // in millions of years
+pub const BIOSPHERE_TIME_STEP: u16 = 10; // in millions of years; catastrophes are rolled at the same pace
+const SPECIATION_CHANCE: f64 = 0.02; // per species, per time step
+const EXTINCTION_CHANCE: f64 = 0.01; // per species, per time step
+const STEP_SEED_MULTIPLIER: u64 = 1_000_003;
+const ANCESTOR_SEED_OFFSET: u64 = 49979687; // keeps the first species independent of the steps after it
 
 pub struct PlanetaryEnvironment {
-    moons: usize,
-    biosphere: Vec<Organism>,
+    name: String,
+    life: bool, // whether anything still lives here
+    moons: Vec<Moon>,
+    phylogeny: Phylogeny,
+    biosphere: Vec<usize>, // living lineages in the phylogeny
+    food_web: FoodWeb, // species in the same order as the biosphere
+    regions: Vec<Region>,
+    civilization: Option<Civilization>,
+    seeding: Option<Seeding>, // where life came from, if it didn't arise here
+    catastrophes: Vec<Catastrophe>, // oldest first
+    metrics: BiosphereMetrics,
 }
 
 impl PlanetaryEnvironment {
     pub fn new (planet: &planet_generator::Planet) -> PlanetaryEnvironment {
-        let mut rng = StdRng::seed_from_u64(planet.get_temperature() as u64);
+        let moons = Self::generate_moons(planet);
+        let (phylogeny, catastrophes) = Self::generate_history(planet);
 
-        let number_of_moons = match planet.get_class() {
+        let biosphere = phylogeny.get_living();
+        let organisms: Vec<&Organism> = biosphere.iter()
+            .map(|&lineage| phylogeny.get_lineage(lineage).unwrap().get_organism())
+            .collect();
+        let food_web = FoodWeb::new(&organisms, planet.get_seed());
+        let regions = Region::generate_regions(planet, &organisms);
+        let metrics = BiosphereMetrics::new(planet, &organisms);
+        let civilization = Civilization::emerge(planet, &phylogeny);
+
+        PlanetaryEnvironment {
+            name: planet.get_name(),
+            life: planet.has_life(),
+            moons,
+            phylogeny,
+            biosphere,
+            food_web,
+            regions,
+            civilization,
+            seeding: planet.get_seeding().cloned(),
+            catastrophes,
+            metrics,
+        }
+    }
+
+    fn choose_number_of_moons(planet: &planet_generator::Planet, rng: &mut impl Rng) -> usize {
+        match planet.get_class() {
             PlanetClass::Rocky => { rng.gen_range(0..=5) }
             PlanetClass::GasGiant => { rng.gen_range(15..=80) }
             PlanetClass::IceGiant => { rng.gen_range(15..=30) }
             PlanetClass::Dwarf => { rng.gen_range(0..=5) }
-        };
-
-        PlanetaryEnvironment {
-            moons: number_of_moons,
-            biosphere: Self::generate_organisms(
-                &planet,
-                rng.gen_range(RICHNESS_MINIMUM..=RICHNESS_MAXIMUM),
-            ),
         }
     }
 
-    fn generate_organisms(planet: &planet_generator::Planet, richness: u8) -> Vec<Organism> {
-        let mut organisms = Vec::new();
+    // The planet's moons, without the rest of the environment
+    pub fn generate_moons(planet: &planet_generator::Planet) -> Vec<Moon> {
+        let mut rng = StdRng::seed_from_u64(planet.get_seed());
+        (0..Self::choose_number_of_moons(planet, &mut rng))
+            .map(|index| Moon::new(planet, index))
+            .collect()
+    }
+
+    // The planet's whole tree of life, without the rest of the environment
+    pub fn generate_phylogeny(planet: &planet_generator::Planet) -> Phylogeny {
+        Self::generate_history(planet).0
+    }
+
+    // The tree of life along with the catastrophes that pruned it
+    fn generate_history(planet: &planet_generator::Planet) -> (Phylogeny, Vec<Catastrophe>) {
+        let mut rng = StdRng::seed_from_u64(planet.get_seed());
+        Self::choose_number_of_moons(planet, &mut rng);
+
+        let time_since_origin = planet.get_origin_of_life()
+            .map_or(0, |origin| planet.get_age() - origin);
+        let richness_maximum = Self::calculate_richness_maximum(time_since_origin);
+
+        Self::simulate_biosphere(planet, rng.gen_range(RICHNESS_MINIMUM..=richness_maximum))
+    }
+
+    fn get_organism(&self, index: usize) -> &Organism {
+        self.phylogeny.get_lineage(self.biosphere[index]).unwrap().get_organism()
+    }
+
+    // Every living species, in the order the food web and regions number them
+    pub fn get_species(&self) -> Vec<&Organism> {
+        (0..self.biosphere.len()).map(|index| self.get_organism(index)).collect()
+    }
+
+    // Builds the scene for stepping into one of the planet's regions
+    pub fn enter_region(&self, index: usize) -> Option<RegionEnvironment> {
+        let region = self.regions.get(index)?;
+        let organisms = region.get_inhabitants().iter()
+            .map(|&i| self.get_organism(i).clone())
+            .collect();
+        let details = region.get_inhabitants().iter()
+            .map(|&i| {
+                let mut s = format!("{} {}", self.phylogeny.get_clade_info(self.biosphere[i]),
+                                    self.food_web.get_species_info(i));
+                if let Some(civilization) = self.civilization.as_ref()
+                    .filter(|civilization| civilization.get_species() == self.biosphere[i]) {
+                    s.push(' ');
+                    s.push_str(&civilization.get_info());
+                }
+                s
+            })
+            .collect();
+        Some(RegionEnvironment::new(region.clone(), organisms, details))
+    }
+
+    pub fn get_moons(&self) -> &Vec<Moon> {
+        &self.moons
+    }
+
+    pub fn get_regions(&self) -> &Vec<Region> {
+        &self.regions
+    }
+
+    pub fn get_civilization(&self) -> Option<&Civilization> {
+        self.civilization.as_ref()
+    }
+
+    pub fn get_seeding(&self) -> Option<&Seeding> {
+        self.seeding.as_ref()
+    }
+
+    pub fn get_catastrophes(&self) -> &Vec<Catastrophe> {
+        &self.catastrophes
+    }
+
+    pub fn get_metrics(&self) -> &BiosphereMetrics {
+        &self.metrics
+    }
+
+    // Biospheres that have had longer to evolve support more species
+    fn calculate_richness_maximum(time_since_origin: u16) -> u8 {
+        let maturity = (time_since_origin as f64 / EVOLUTION_TIME_FOR_FULL_RICHNESS as f64).min(1.0);
+        RICHNESS_MINIMUM + ((RICHNESS_MAXIMUM - RICHNESS_MINIMUM) as f64 * maturity).round() as u8
+    }
 
-        if planet.get_habitability() {
-            for i in 1..richness {
-                organisms.push(Organism::new(planet, (planet.get_temperature() + i as u16) as u64));
+    // Life arises at the planet's origin of life as a last universal common ancestor, or arrives
+    // as a descendant of a traveller from another world. Its descendants then diversify and go
+    // extinct one time step at a time, with the occasional catastrophe cutting them back. Each
+    // step is seeded from the planet and the step's age, so a biosphere generated at a later age
+    // matches one fast-forwarded to it.
+    fn simulate_biosphere(planet: &planet_generator::Planet, carrying_capacity: u8) -> (Phylogeny, Vec<Catastrophe>) {
+        let mut phylogeny = Phylogeny::new(planet.get_age());
+        let mut catastrophes = Vec::new();
+
+        if let Some(origin) = planet.get_origin_of_life().filter(|&origin| origin <= planet.get_age()) {
+            // The ancestor appears when life does, even if that is too recent for a whole time
+            // step to have passed since
+            let mut rng = StdRng::seed_from_u64(planet.get_seed().wrapping_add(ANCESTOR_SEED_OFFSET));
+            let ancestor = match planet.get_seeding() {
+                Some(seeding) => seeding.get_ancestor().descend(planet, origin, rng.gen()),
+                None => Organism::new(planet, origin, rng.gen()),
+            };
+            phylogeny.add_common_ancestor(ancestor, origin);
+
+            let first_step = origin.div_ceil(BIOSPHERE_TIME_STEP);
+            let last_step = planet.get_age() / BIOSPHERE_TIME_STEP;
+
+            for step in first_step..last_step {
+                let time = step * BIOSPHERE_TIME_STEP;
+                let mut rng = StdRng::seed_from_u64(
+                    planet.get_seed().wrapping_mul(STEP_SEED_MULTIPLIER).wrapping_add(step as u64),
+                );
+
+                let living = phylogeny.get_living();
+
+                // The last surviving lineage persists for as long as the planet stays habitable
+                if living.len() > 1 {
+                    for &lineage in &living {
+                        if rng.gen_bool(EXTINCTION_CHANCE) {
+                            phylogeny.go_extinct(lineage, time);
+                        }
+                    }
+                }
+
+                // Catastrophes strike even a lone surviving lineage, though only a runaway
+                // greenhouse, which takes the ocean with it, can wipe it out
+                let (events, mut catastrophe_rng) = Catastrophe::roll_step(planet, step);
+                for (kind, severity) in events {
+                    catastrophes.push(match kind {
+                        CatastropheKind::RunawayGreenhouse => Catastrophe::sterilize(kind, &mut phylogeny, time),
+                        _ => Catastrophe::strike(kind, severity, &mut phylogeny, time, &mut catastrophe_rng),
+                    });
+                }
+                if planet.get_runaway_greenhouse() == Some(time) {
+                    break;
+                }
+                let living = phylogeny.get_living();
+
+                let mut richness = living.len();
+                for parent in living {
+                    if richness < carrying_capacity as usize && rng.gen_bool(SPECIATION_CHANCE) {
+                        let organism = phylogeny.get_lineage(parent).unwrap()
+                            .get_organism().descend(planet, time, rng.gen());
+                        phylogeny.branch(parent, organism, time);
+                        richness += 1;
+                    }
+                }
             }
         }
-        organisms
+        (phylogeny, catastrophes)
     }
 
 }
@@ -51,23 +235,111 @@ impl Scene for PlanetaryEnvironment {
         SceneName::PlanetaryEnvironment
     }
 
-    fn get_system_info(&self) -> String {
-        let mut s = String::new();
-        for i in 0..self.biosphere.len() {
-            s.push_str(&self.biosphere[i].get_size().to_string());
-            s.push_str("_");
-            s.push_str(&i.to_string());
-            s.push_str(" ");
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_description(&self) -> String {
+        let mut history = String::new();
+        if let Some(seeding) = &self.seeding {
+            history.push_str(&format!("Life here descends from {}, which came from {}.\n",
+                                      seeding.get_ancestor().get_name(), seeding.get_source()));
         }
-        format!("There are {} moons and {}", &self.moons, s)
+        if let Some(civilization) = &self.civilization {
+            history.push_str(&format!("{}\n", civilization.get_info()));
+        }
+        if !self.biosphere.is_empty() {
+            history.push_str(&format!("{}\n", self.metrics.get_info()));
+        }
+        format!("{}There are {} moons and {} living species.", history, self.moons.len(), self.biosphere.len())
     }
 
-    fn get_unit_info(&self, index: usize) -> String {
-        if index < self.biosphere.len() {
-            self.biosphere[index].get_info()
+    fn get_properties(&self) -> Vec<Property> {
+        let mut properties = vec![
+            Property::text("Planet", &self.name),
+            Property::integer("Moons", self.moons.len() as i64),
+            Property::integer("Lineages", self.phylogeny.get_lineage_count() as i64),
+            Property::integer("Catastrophes", self.catastrophes.len() as i64),
+        ];
+        properties.extend(biosphere_metrics_generator::describe_metrics(&self.metrics));
+        if let Some(seeding) = &self.seeding {
+            properties.push(Property::text("Seeded from", seeding.get_source()));
+            properties.push(Property::text("Seeded by", seeding.get_route()));
         }
-        else {
-            format!("Invalid coordinates.")
+        if let Some(civilization) = &self.civilization {
+            properties.push(Property::text("Civilization", civilization.get_name()));
+            properties.push(Property::flag("Civilization active", civilization.is_active()));
+            properties.push(Property::number("Kardashev level", civilization.get_level()));
         }
+        properties
+    }
+
+    // The regions, then the moons
+    fn get_units(&self) -> Vec<Unit> {
+        let regions = self.regions.iter().enumerate()
+            .map(|(index, region)| {
+                let names: Vec<&str> = region.get_inhabitants().iter()
+                    .map(|&i| self.get_organism(i).get_name().as_str())
+                    .collect();
+                Unit::new(Level::Region(index), region.get_biome(), region.get_biome(), vec![
+                    Property::number("Area", region.get_area()),
+                    Property::integer("Temperature (K)", region.get_temperature()),
+                    Property::integer("Species", names.len() as i64),
+                    Property::text("Inhabitants", names.join(", ")),
+                ], format!("{} {}", region.get_info(), names.join(", ")))
+            });
+        let moons = self.moons.iter().enumerate()
+            .map(|(index, moon)| Unit::new(Level::Moon(index), "Moon", moon.get_designation(),
+                                           moon_generator::describe_moon(moon), moon.get_info())
+                .at(self.regions.len() + index));
+        regions.chain(moons).collect()
+    }
+
+    fn get_phylogeny(&self) -> Option<&Phylogeny> {
+        Some(&self.phylogeny)
+    }
+
+    fn get_food_web(&self) -> Option<&FoodWeb> {
+        Some(&self.food_web)
+    }
+
+    fn get_food_web_mut(&mut self) -> Option<&mut FoodWeb> {
+        Some(&mut self.food_web)
+    }
+
+    fn get_metrics(&self) -> Option<&BiosphereMetrics> {
+        Some(&self.metrics)
+    }
+
+    fn enter(&self, level: &Level) -> Option<Box<dyn Scene>> {
+        match *level {
+            Level::Region(index) => Some(Box::new(self.enter_region(index)?)),
+            Level::Moon(index) => Some(Box::new(MoonEnvironment::new(self.moons.get(index)?.clone()))),
+            _ => None,
+        }
+    }
+
+    fn get_child_level(&self, index: usize) -> Option<Level> {
+        if index < self.regions.len() {
+            Some(Level::Region(index))
+        } else {
+            Some(Level::Moon(index - self.regions.len()))
+        }
+    }
+
+    fn get_discoveries(&self) -> Vec<String> {
+        let mut discoveries = Vec::new();
+        if self.life {
+            discoveries.push(format!("Life on {}", self.name));
+        }
+        if let Some(civilization) = &self.civilization {
+            discoveries.push(format!("The civilization of {} on {}", civilization.get_name(), self.name));
+        }
+        discoveries
+    }
+
+    // Every catastrophe that struck while there was life here, oldest first
+    fn get_catastrophes(&self) -> Option<&Vec<Catastrophe>> {
+        if self.phylogeny.get_lineage_count() == 0 { None } else { Some(&self.catastrophes) }
     }
 }
\ No newline at end of file
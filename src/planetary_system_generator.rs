@@ -1,34 +1,80 @@
 use rand::prelude::*;
 
-use crate::game_manager::{Scene, SceneName};
+use crate::galaxy_generator::GalaxySettings;
+use crate::game_manager::{Level, Property, Scene, SceneName, Unit};
+use crate::panspermia_generator;
 use crate::planet_generator;
+use crate::planetary_environment_generator::PlanetaryEnvironment;
 use crate::star_generator;
 
 const PLANET_NUMBER_MINIMUM: u8 = 3;
 const PLANET_NUMBER_MAXIMUM: u8 = 12;
 
 pub struct PlanetarySystem {
+    star: star_generator::Star,
     planets: Vec<planet_generator::Planet>,
 }
 
 impl PlanetarySystem {
-    pub fn new(star: &star_generator::Star) -> Self {
-        let mut rng = StdRng::seed_from_u64(star.get_age() as u64);
-
-        PlanetarySystem {
-            planets: Self::generate_planets(
-                &star,
-                rng.gen_range(PLANET_NUMBER_MINIMUM..=PLANET_NUMBER_MAXIMUM),
-            ),
-        }
+    // Planets are open to life from the rest of the galaxy, which needs its size and age
+    pub fn new(star: &star_generator::Star, galaxy: &GalaxySettings, elapsed_time: u16) -> Self {
+        let planets = (1..Self::count_planets(star))
+            .map(|distance| panspermia_generator::generate_planet(star, distance, galaxy, elapsed_time))
+            .collect();
+        PlanetarySystem { star: star.clone(), planets }
     }
 
-    fn generate_planets(star: &star_generator::Star, count: u8) -> Vec<planet_generator::Planet> {
-        let mut planets = Vec::new();
-        for i in 1..count {
-            planets.push(planet_generator::Planet::new(star, i));
-        }
-        planets
+    // The planets as they would be in isolation, with only the life that arose on them
+    pub fn generate_native_planets(star: &star_generator::Star) -> Vec<planet_generator::Planet> {
+        (1..Self::count_planets(star))
+            .map(|distance| planet_generator::Planet::new(star, distance))
+            .collect()
+    }
+
+    fn count_planets(star: &star_generator::Star) -> u8 {
+        let mut rng = StdRng::seed_from_u64(star.get_seed());
+        rng.gen_range(PLANET_NUMBER_MINIMUM..=PLANET_NUMBER_MAXIMUM)
+    }
+
+    pub fn get_star(&self) -> &star_generator::Star {
+        &self.star
+    }
+
+    pub fn get_planets(&self) -> &Vec<planet_generator::Planet> {
+        &self.planets
+    }
+
+    // A planet as a unit of its system, at the given index
+    pub fn describe_planet(index: usize, planet: &planet_generator::Planet) -> Unit {
+        let life = planet.get_origin_of_life()
+            .filter(|_| planet.has_life())
+            .map_or(0, |origin| planet.get_age() - origin);
+        let properties = vec![
+            Property::text("Designation", planet.get_designation()),
+            Property::text("Proper name", planet.get_proper_name().map_or("", |name| name.as_str())),
+            Property::integer("Seed", planet.get_seed() as i64),
+            Property::text("Class", planet.get_class()),
+            Property::integer("Distance (AU)", planet.get_distance()),
+            Property::integer("Mass", planet.get_mass()),
+            Property::number("Surface gravity (g)", planet.get_surface_gravity()),
+            Property::integer("Temperature (K)", planet.get_temperature()),
+            Property::integer("Pressure", planet.get_pressure()),
+            Property::text("Ocean", planet.get_ocean()),
+            Property::number("Ocean coverage", planet.get_ocean_coverage()),
+            Property::integer("Axial tilt (°)", planet.get_axial_tilt()),
+            Property::flag("Magnetic field", planet.get_magnetic_field()),
+            Property::flag("Free oxygen", planet.has_free_oxygen_at(planet.get_age())),
+            Property::integer("Age (Myr)", planet.get_age()),
+            Property::number("Star mass (M☉)", planet.get_star_mass()),
+            Property::flag("Habitable", planet.get_habitability()),
+            Property::integer("Habitable for (Myr)", planet.get_habitable_duration()),
+            Property::flag("Runaway greenhouse", planet.get_runaway_greenhouse().is_some()),
+            Property::number("Abiogenesis probability", planet.get_abiogenesis_probability()),
+            Property::flag("Life", planet.has_life()),
+            Property::integer("Life for (Myr)", life),
+            Property::flag("Seeded from elsewhere", planet.get_seeding().is_some()),
+        ];
+        Unit::new(Level::Planet(index), planet.get_class(), planet.get_name(), properties, planet.get_info())
     }
 }
 
@@ -37,23 +83,40 @@ impl Scene for PlanetarySystem {
         SceneName::PlanetarySystem
     }
 
-    fn get_system_info(&self) -> String {
-        let mut s = String::new();
-        for i in 0..self.planets.len() {
-            s.push_str(&self.planets[i].get_class().to_string());
-            s.push_str("_");
-            s.push_str(&i.to_string());
-            s.push_str(" ");
-        }
-        format!("There are {} planets: {}", &self.planets.len(), s)
+    fn get_name(&self) -> String {
+        self.star.get_name()
     }
 
-    fn get_unit_info(&self, index: usize) -> String {
-        if index < self.planets.len() {
-            self.planets[index].get_info()
-        }
-        else {
-            format!("Invalid coordinates.")
+    fn get_description(&self) -> String {
+        format!("There are {} planets around {}.", self.planets.len(), self.star.get_name())
+    }
+
+    fn get_properties(&self) -> Vec<Property> {
+        vec![
+            Property::text("Star", self.star.get_name()),
+            Property::text("Color", self.star.get_color()),
+            Property::integer("Planets", self.planets.len() as i64),
+        ]
+    }
+
+    fn get_units(&self) -> Vec<Unit> {
+        self.planets.iter().enumerate()
+            .map(|(index, planet)| Self::describe_planet(index, planet))
+            .collect()
+    }
+
+    fn enter(&self, level: &Level) -> Option<Box<dyn Scene>> {
+        match *level {
+            Level::Planet(index) => Some(Box::new(PlanetaryEnvironment::new(self.planets.get(index)?))),
+            _ => None,
         }
     }
+
+    fn get_child_level(&self, index: usize) -> Option<Level> {
+        Some(Level::Planet(index))
+    }
+
+    fn get_planetary_system(&self) -> Option<&PlanetarySystem> {
+        Some(self)
+    }
 }
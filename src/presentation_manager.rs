@@ -0,0 +1,238 @@
+use serde_json::{json, Map};
+use strum_macros::{Display, EnumString};
+
+use crate::biosphere_metrics_generator;
+use crate::catastrophe_generator;
+use crate::catastrophe_generator::Catastrophe;
+use crate::food_web_generator::FoodWeb;
+use crate::game_manager::{Game, Level, Property, Unit, Value};
+
+// Turns the data scenes return into the text the command line prints. Other frontends read the
+// same units and properties and lay them out their own way.
+
+#[derive(Display, EnumString, Clone, Copy, PartialEq)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum Format {
+    Text, // prose and tables, for people
+    Json, // one object per line, for scripts
+}
+
+// Prints what commands return in the chosen format
+pub struct Printer {
+    format: Format,
+}
+
+impl Printer {
+    pub fn new(format: Format) -> Printer {
+        Printer { format }
+    }
+
+    pub fn get_format(&self) -> Format {
+        self.format
+    }
+
+    pub fn scene(&self, game: &Game) {
+        match self.format {
+            Format::Text => println!("{}", format_scene(game)),
+            Format::Json => println!("{}", scene_to_json(game)),
+        }
+    }
+
+    pub fn unit(&self, unit: Option<&Unit>) {
+        match (self.format, unit) {
+            (Format::Json, Some(unit)) => println!("{}", unit_to_json(unit)),
+            _ => self.message(&format_unit(unit)),
+        }
+    }
+
+    pub fn list(&self, units: &[Unit], filtered: bool) {
+        match self.format {
+            Format::Text => println!("{}", format_list(units, filtered)),
+            Format::Json => println!("{}", json!({ "units": units.iter().map(unit_to_json).collect::<Vec<_>>() })),
+        }
+    }
+
+    pub fn comparison(&self, pair: Option<(Unit, Unit)>) {
+        match (self.format, pair) {
+            (Format::Json, Some((first, second))) => println!("{}", json!({
+                "compare": [unit_to_json(&first), unit_to_json(&second)]
+            })),
+            (_, pair) => self.message(&format_comparison(pair)),
+        }
+    }
+
+    pub fn search(&self, units: &[Unit]) {
+        match self.format {
+            Format::Text => println!("{}", format_search(units)),
+            Format::Json => println!("{}", json!({ "found": units.iter().map(unit_to_json).collect::<Vec<_>>() })),
+        }
+    }
+
+    // A planet's catastrophes as of the present, oldest first
+    pub fn timeline(&self, history: Option<(u16, &[Catastrophe])>) {
+        match (self.format, history) {
+            (Format::Json, Some((present, catastrophes))) => println!("{}", json!({
+                "timeline": catastrophes.iter()
+                    .map(|catastrophe| properties_to_json(&catastrophe_generator::describe_catastrophe(catastrophe, present)))
+                    .collect::<Vec<_>>()
+            })),
+            (_, history) => self.message(&format_timeline(history)),
+        }
+    }
+
+    // Each species with its population, and who eats whom by index into the species
+    pub fn food_web(&self, food_web: Option<&FoodWeb>) {
+        match (self.format, food_web) {
+            (Format::Json, Some(food_web)) => println!("{}", json!({
+                "food_web": {
+                    "species": food_web.get_species().iter().zip(food_web.get_trophic_levels())
+                        .map(|(species, level)| json!({
+                            "name": species.get_name(),
+                            "role": species.get_role().to_string(),
+                            "population": species.get_population(),
+                            "trophic_level": level,
+                        }))
+                        .collect::<Vec<_>>(),
+                    "links": food_web.get_links().iter()
+                        .map(|link| json!({ "prey": link.get_prey(), "predator": link.get_predator() }))
+                        .collect::<Vec<_>>(),
+                    "stability": food_web.get_stability().to_string(),
+                }
+            })),
+            (_, Some(food_web)) => self.message(&food_web.get_info()),
+            (_, None) => self.message("There is no food web here."),
+        }
+    }
+
+    // Anything without a structure of its own is passed on as it would be read
+    pub fn message(&self, text: &str) {
+        match self.format {
+            Format::Text => println!("{}", text),
+            Format::Json => println!("{}", json!({ "message": text })),
+        }
+    }
+}
+
+// The breadcrumbs, the scene's description, and its units labelled with the index that picks them
+pub fn format_scene(game: &Game) -> String {
+    let scene = game.get_scene();
+    let units: Vec<String> = scene.get_units().iter()
+        .map(|unit| format!("{}_{}", unit.get_kind(), unit.get_index()))
+        .collect();
+    let mut text = format!("{}\n{}", game.get_breadcrumbs(), scene.get_description());
+    if !units.is_empty() {
+        text.push_str(&format!("\n{}", units.join(" ")));
+    }
+    text
+}
+
+pub fn format_unit(unit: Option<&Unit>) -> String {
+    match unit {
+        Some(unit) => unit.get_description().clone(),
+        None => "Invalid coordinates.".to_string(),
+    }
+}
+
+pub fn format_list(units: &[Unit], filtered: bool) -> String {
+    if units.is_empty() {
+        return if filtered { "Nothing here matches." } else { "There is nothing here to list." }.to_string();
+    }
+    units.iter()
+        .map(|unit| match unit.get_kind() == unit.get_name() {
+            true => format!("{:>4}  {}", unit.get_index(), unit.get_name()),
+            false => format!("{:>4}  {} {}", unit.get_index(), unit.get_kind(), unit.get_name()),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Sets the properties of two units side by side, one row per property
+pub fn format_comparison(pair: Option<(Unit, Unit)>) -> String {
+    let Some((first, second)) = pair else {
+        return "There is nothing to compare at those coordinates.".to_string();
+    };
+    let mut rows = vec![("".to_string(), format!("#{}", first.get_index()), format!("#{}", second.get_index())),
+                        ("Name".to_string(), first.get_name().clone(), second.get_name().clone())];
+    for property in first.get_properties() {
+        let other = second.get_property(property.get_name())
+            .map_or("-".to_string(), |other| other.to_string());
+        rows.push((property.get_name().clone(), property.get_value().to_string(), other));
+    }
+    let label_width = rows.iter().map(|row| row.0.chars().count()).max().unwrap_or(0);
+    let value_width = rows.iter().map(|row| row.1.chars().count()).max().unwrap_or(0);
+    rows.iter()
+        .map(|row| format!("{:<label$}  {:<value$}  {}", row.0, row.1, row.2,
+                           label = label_width, value = value_width))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Search results as paths to each planet found, with enough to tell them apart
+pub fn format_search(units: &[Unit]) -> String {
+    if units.is_empty() {
+        return "Nothing turned up anywhere in the galaxy.".to_string();
+    }
+    let results: Vec<String> = units.iter()
+        .map(|unit| {
+            let value = |name: &str| unit.get_property(name).map_or(String::new(), |value| value.to_string());
+            format!("{}  {}: {} ocean, {} K", unit.get_id(), unit.get_name(), value("Ocean"), value("Temperature (K)"))
+        })
+        .collect();
+    format!("Found {}:\n{}", results.len(), results.join("\n"))
+}
+
+pub fn format_timeline(history: Option<(u16, &[Catastrophe])>) -> String {
+    match history {
+        None => "There is no history of life here.".to_string(),
+        Some((_, [])) => "No catastrophes have struck since life began here.".to_string(),
+        Some((present, catastrophes)) => catastrophes.iter()
+            .map(|catastrophe| catastrophe.get_info(present))
+            .collect::<Vec<String>>()
+            .join("\n"),
+    }
+}
+
+pub fn value_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Text(text) => json!(text),
+        Value::Integer(integer) => json!(integer),
+        Value::Number(number) => json!(number),
+        Value::Flag(flag) => json!(flag),
+    }
+}
+
+fn properties_to_json(properties: &[Property]) -> serde_json::Value {
+    let properties: Map<String, serde_json::Value> = properties.iter()
+        .map(|property| (property.get_name().clone(), value_to_json(property.get_value())))
+        .collect();
+    serde_json::Value::Object(properties)
+}
+
+pub fn unit_to_json(unit: &Unit) -> serde_json::Value {
+    json!({
+        "id": unit.get_id(),
+        "kind": unit.get_kind(),
+        "name": unit.get_name(),
+        "properties": properties_to_json(unit.get_properties()),
+        "description": unit.get_description(),
+    })
+}
+
+pub fn scene_to_json(game: &Game) -> serde_json::Value {
+    let scene = game.get_scene();
+    let mut object = json!({
+        "id": Level::format_path(game.get_path()),
+        "scene": scene.get_scene_name().to_string(),
+        "name": scene.get_name(),
+        "breadcrumbs": game.get_scene_names(),
+        "properties": properties_to_json(&scene.get_properties()),
+        "description": scene.get_description(),
+        "units": game.get_units().iter().map(unit_to_json).collect::<Vec<_>>(),
+    });
+    // Biosphere figures also come on their own, so scripts can compare worlds without picking
+    // them out of the other properties
+    if let Some(metrics) = scene.get_metrics() {
+        object["metrics"] = properties_to_json(&biosphere_metrics_generator::describe_metrics(metrics));
+    }
+    object
+}
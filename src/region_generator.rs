@@ -0,0 +1,247 @@
+use strum_macros::Display;
+
+use crate::game_manager::{Level, Property, Scene, SceneName, Unit};
+use crate::organism_generator::{EnergySource, Habitat, Organism};
+use crate::planet_generator;
+use crate::planet_generator::Ocean;
+
+const LATITUDINAL_GRADIENT: f64 = 80.0; // in kelvins between equator and pole on an untilted world
+const MINIMUM_GRADIENT_FRACTION: f64 = 0.25; // steep tilts even out the seasons, but never entirely
+const SHALLOW_SEA_FRACTION: f64 = 0.3; // of the open ocean
+const HYDROTHERMAL_VENT_FRACTION: f64 = 0.01; // of the open ocean, on geologically active worlds
+const HYDROTHERMAL_VENT_HEATING: u16 = 80; // in kelvins
+const SHALLOW_SEA_WARMING: u16 = 5; // in kelvins
+const DESERT_FRACTION_MINIMUM: f64 = 0.1; // of the land, even on the wettest worlds
+const DESERT_FRACTION_PER_DRY_AREA: f64 = 0.8; // drier worlds have more desert
+const MINIMUM_REGION_AREA: f64 = 0.005;
+
+#[derive(Display, Clone, PartialEq)]
+pub enum Biome {
+    OceanDepths,
+    HydrothermalVents,
+    ShallowSeas,
+    IceCaps,
+    Deserts,
+    Forests,
+}
+
+// A part of a planet's surface; area is the fraction of the surface it covers
+#[derive(Clone)]
+pub struct Region {
+    biome: Biome,
+    area: f64,
+    temperature: u16,
+    inhabitants: Vec<usize>, // indices into the planet's biosphere
+}
+
+impl Region {
+    // Splits a planet with an ocean into biomes from its temperature bands, ocean coverage and
+    // tilt, and places each organism in the biomes its habitat reaches
+    pub fn generate_regions(planet: &planet_generator::Planet, organisms: &[&Organism]) -> Vec<Region> {
+        let (freezing, boiling) = match planet.get_ocean() {
+            Ocean::Water => (273.0, 373.0),
+            Ocean::Ammonia => (196.0, 240.0),
+            Ocean::None => return Vec::new(),
+        };
+
+        let temperature = planet.get_temperature() as f64;
+        let gradient = LATITUDINAL_GRADIENT
+            * (1.0 - planet.get_axial_tilt() as f64 / 90.0).max(MINIMUM_GRADIENT_FRACTION);
+        let equator = temperature + gradient / 2.0;
+        let pole = temperature - gradient / 2.0;
+
+        // Latitudes colder than the solvent's freezing point are frozen over
+        let ice = ((freezing - pole) / gradient).clamp(0.0, 1.0);
+        let ocean = planet.get_ocean_coverage() * (1.0 - ice);
+        let land = (1.0 - planet.get_ocean_coverage()) * (1.0 - ice);
+        let vents = if planet.get_magnetic_field() { ocean * HYDROTHERMAL_VENT_FRACTION } else { 0.0 };
+        let shallows = ocean * SHALLOW_SEA_FRACTION;
+        let deserts = land * (DESERT_FRACTION_MINIMUM
+            + DESERT_FRACTION_PER_DRY_AREA * (1.0 - planet.get_ocean_coverage())).min(1.0);
+
+        let candidates = [
+            (Biome::OceanDepths, ocean - shallows - vents, temperature),
+            (Biome::HydrothermalVents, vents, temperature + HYDROTHERMAL_VENT_HEATING as f64),
+            (Biome::ShallowSeas, shallows, temperature + SHALLOW_SEA_WARMING as f64),
+            (Biome::IceCaps, ice, pole.min(freezing)),
+            (Biome::Deserts, deserts, equator.min(boiling)),
+            (Biome::Forests, land - deserts, temperature),
+        ];
+
+        let mut regions: Vec<Region> = candidates.into_iter()
+            .filter(|candidate| candidate.1 >= MINIMUM_REGION_AREA)
+            .map(|(biome, area, temperature)| {
+                let inhabitants = (0..organisms.len())
+                    .filter(|&i| Self::inhabits(organisms[i], &biome))
+                    .collect();
+                Region { biome, area, temperature: temperature.max(0.0) as u16, inhabitants }
+            })
+            .collect();
+
+        // Organisms whose preferred biomes are missing eke out a living in the largest region
+        let largest = (0..regions.len()).max_by(|&a, &b| regions[a].area.total_cmp(&regions[b].area));
+        if let Some(largest) = largest {
+            for i in 0..organisms.len() {
+                if regions.iter().all(|region| !region.inhabitants.contains(&i)) {
+                    regions[largest].inhabitants.push(i);
+                }
+            }
+            regions[largest].inhabitants.sort();
+        }
+        regions
+    }
+
+    fn inhabits(organism: &Organism, biome: &Biome) -> bool {
+        let chemotroph = *organism.get_energy_source() == EnergySource::Chemotroph;
+        match organism.get_habitat() {
+            Habitat::Pelagic => matches!(biome, Biome::OceanDepths | Biome::ShallowSeas),
+            Habitat::Benthic => match biome {
+                Biome::HydrothermalVents => chemotroph,
+                Biome::OceanDepths | Biome::ShallowSeas => true,
+                _ => false,
+            },
+            Habitat::Subsurface => match biome {
+                Biome::HydrothermalVents => chemotroph,
+                Biome::Deserts | Biome::Forests | Biome::IceCaps => true,
+                _ => false,
+            },
+            Habitat::Terrestrial => matches!(biome, Biome::Deserts | Biome::Forests | Biome::IceCaps),
+            Habitat::Aerial => matches!(biome, Biome::Deserts | Biome::Forests | Biome::ShallowSeas),
+        }
+    }
+
+    pub fn get_info(&self) -> String {
+        format!("The {} cover {:.1}% of the planet at around {} K and are home to {} species.",
+                &self.biome, self.area * 100.0, &self.temperature, self.inhabitants.len())
+    }
+
+    pub fn get_biome(&self) -> &Biome {
+        &self.biome
+    }
+
+    pub fn get_area(&self) -> f64 {
+        self.area
+    }
+
+    pub fn get_temperature(&self) -> u16 {
+        self.temperature
+    }
+
+    pub fn get_inhabitants(&self) -> &Vec<usize> {
+        &self.inhabitants
+    }
+}
+
+// The measurements shared by an organism listed in its region, taken up as a specimen, and exported
+pub fn describe_organism(organism: &Organism) -> Vec<Property> {
+    let senses: Vec<String> = organism.get_senses().iter().map(|sense| sense.to_string()).collect();
+    let body_plan = organism.get_body_plan();
+    vec![
+        Property::text("Genus", organism.get_genus()),
+        Property::text("Size", organism.get_size()),
+        Property::text("Organization", organism.get_organization()),
+        Property::text("Symmetry", organism.get_symmetry()),
+        Property::text("Structure", organism.get_structure()),
+        Property::text("Solvent", organism.get_solvent()),
+        Property::text("Metabolism", organism.get_metabolism()),
+        Property::text("Energy source", organism.get_energy_source()),
+        Property::text("Trophic role", organism.get_trophic_role()),
+        Property::text("Habitat", organism.get_habitat()),
+        Property::text("Reproduction", organism.get_reproduction()),
+        Property::text("Locomotion", organism.get_locomotion()),
+        Property::text("Senses", senses.join(", ")),
+        Property::number("Length (m)", body_plan.get_length()),
+        Property::integer("Segments", body_plan.get_segments()),
+        Property::integer("Appendages", body_plan.get_appendages().iter().map(|appendage| appendage.1 as i64).sum::<i64>()),
+        Property::text("Skeleton", body_plan.get_skeleton()),
+        Property::text("Covering", body_plan.get_covering()),
+    ]
+}
+
+// The scene inside a single region, where the units are the organisms living there
+pub struct RegionEnvironment {
+    region: Region,
+    organisms: Vec<Organism>,
+    details: Vec<String>, // each organism's ancestry and place in the food web
+}
+
+impl RegionEnvironment {
+    pub fn new(region: Region, organisms: Vec<Organism>, details: Vec<String>) -> RegionEnvironment {
+        RegionEnvironment { region, organisms, details }
+    }
+}
+
+impl Scene for RegionEnvironment {
+    fn get_scene_name(&self) -> SceneName {
+        SceneName::Region
+    }
+
+    fn get_name(&self) -> String {
+        self.region.get_biome().to_string()
+    }
+
+    fn get_description(&self) -> String {
+        self.region.get_info()
+    }
+
+    fn get_properties(&self) -> Vec<Property> {
+        vec![
+            Property::text("Biome", self.region.get_biome()),
+            Property::number("Area", self.region.get_area()),
+            Property::integer("Temperature (K)", self.region.get_temperature()),
+            Property::integer("Species", self.organisms.len() as i64),
+        ]
+    }
+
+    fn get_units(&self) -> Vec<Unit> {
+        self.organisms.iter().zip(&self.details).enumerate()
+            .map(|(index, (organism, details))| {
+                Unit::new(Level::Organism(index), organism.get_size(), organism.get_name(),
+                          describe_organism(organism), format!("{} {}", organism.get_info(), details))
+            })
+            .collect()
+    }
+
+    fn enter(&self, level: &Level) -> Option<Box<dyn Scene>> {
+        match *level {
+            Level::Organism(index) if index < self.organisms.len() => {
+                Some(Box::new(Specimen::new(self.organisms[index].clone(), self.details[index].clone())))
+            }
+            _ => None,
+        }
+    }
+
+    fn get_child_level(&self, index: usize) -> Option<Level> {
+        Some(Level::Organism(index))
+    }
+}
+
+// A single organism taken up for a closer look, the innermost level of exploration
+pub struct Specimen {
+    organism: Organism,
+    details: String, // its ancestry and place in the food web
+}
+
+impl Specimen {
+    pub fn new(organism: Organism, details: String) -> Specimen {
+        Specimen { organism, details }
+    }
+}
+
+impl Scene for Specimen {
+    fn get_scene_name(&self) -> SceneName {
+        SceneName::Organism
+    }
+
+    fn get_name(&self) -> String {
+        self.organism.get_name().clone()
+    }
+
+    fn get_description(&self) -> String {
+        format!("{} {}", self.organism.get_info(), self.details)
+    }
+
+    fn get_properties(&self) -> Vec<Property> {
+        describe_organism(&self.organism)
+    }
+}
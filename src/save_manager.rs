@@ -0,0 +1,147 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::galaxy_generator::{GalaxySettings, GalaxyShape};
+
+const SAVE_VERSION: u32 = 1; // bump whenever the format changes, and teach upgrade() the old one
+
+// Everything needed to put a player back where they were. The galaxy itself is never stored:
+// generation is deterministic, so its settings, the time and the path taken are enough to rebuild it.
+#[derive(Serialize, Deserialize)]
+pub struct SaveFile {
+    version: u32,
+    galaxy_seed: u64,
+    galaxy_size: u64,
+    galaxy_shape: GalaxyShape,
+    elapsed_time: u16, // in millions of years since the game began
+    path: String, // from the galaxy inward, e.g. "/star/42/planet/3"
+    discoveries: Vec<String>,
+    notes: Vec<String>,
+}
+
+impl SaveFile {
+    pub fn new(galaxy: &GalaxySettings, elapsed_time: u16, path: String, discoveries: Vec<String>,
+               notes: Vec<String>) -> SaveFile {
+        SaveFile {
+            version: SAVE_VERSION,
+            galaxy_seed: galaxy.get_seed(),
+            galaxy_size: galaxy.get_size(),
+            galaxy_shape: *galaxy.get_shape(),
+            elapsed_time,
+            path,
+            discoveries,
+            notes,
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<SaveFile, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        let value: serde_json::Value = serde_json::from_str(&contents)?;
+        let version = value.get("version")
+            .and_then(|version| version.as_u64())
+            .ok_or("the save has no version")? as u32;
+        Self::upgrade(version, value)
+    }
+
+    // Brings a save up to the current format; each older format gets an arm here when the version
+    // is bumped. Saves from newer releases are refused rather than half-read.
+    fn upgrade(version: u32, value: serde_json::Value) -> Result<SaveFile, Box<dyn Error>> {
+        match version {
+            SAVE_VERSION => Ok(serde_json::from_value(value)?),
+            _ if version > SAVE_VERSION => Err(format!("the save is from a newer version ({}) of the game", version).into()),
+            _ => Err(format!("the save's version ({}) is not recognised", version).into()),
+        }
+    }
+
+    pub fn get_galaxy_seed(&self) -> u64 {
+        self.galaxy_seed
+    }
+
+    pub fn get_galaxy_size(&self) -> u64 {
+        self.galaxy_size
+    }
+
+    pub fn get_galaxy_shape(&self) -> &GalaxyShape {
+        &self.galaxy_shape
+    }
+
+    pub fn get_elapsed_time(&self) -> u16 {
+        self.elapsed_time
+    }
+
+    pub fn get_path(&self) -> &String {
+        &self.path
+    }
+
+    pub fn get_discoveries(&self) -> &Vec<String> {
+        &self.discoveries
+    }
+
+    pub fn get_notes(&self) -> &Vec<String> {
+        &self.notes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save() -> SaveFile {
+        let galaxy = GalaxySettings::new(7, 250, GalaxyShape::Ring);
+        SaveFile::new(&galaxy, 120, "/star/42/planet/3/moon/1".to_string(),
+                      vec!["Life on HD 812710 c".to_string()], vec!["Look again later".to_string()])
+    }
+
+    fn value_with_version(version: u32) -> serde_json::Value {
+        let mut value = serde_json::to_value(save()).unwrap();
+        value["version"] = version.into();
+        value
+    }
+
+    #[test]
+    fn saves_read_back_as_they_were_written() {
+        let path = std::env::temp_dir().join(format!("astrobiology-save-test-{}.json", std::process::id()));
+        save().write(&path).unwrap();
+        let read = SaveFile::read(&path);
+        fs::remove_file(&path).unwrap();
+
+        let read = read.unwrap();
+        assert_eq!(read.get_galaxy_seed(), 7);
+        assert_eq!(read.get_galaxy_size(), 250);
+        assert!(*read.get_galaxy_shape() == GalaxyShape::Ring);
+        assert_eq!(read.get_elapsed_time(), 120);
+        assert_eq!(read.get_path(), "/star/42/planet/3/moon/1");
+        assert_eq!(read.get_discoveries(), &vec!["Life on HD 812710 c".to_string()]);
+        assert_eq!(read.get_notes(), &vec!["Look again later".to_string()]);
+    }
+
+    #[test]
+    fn the_current_version_upgrades_to_itself() {
+        let upgraded = SaveFile::upgrade(SAVE_VERSION, value_with_version(SAVE_VERSION)).unwrap();
+        assert_eq!(serde_json::to_value(upgraded).unwrap(), serde_json::to_value(save()).unwrap());
+    }
+
+    #[test]
+    fn unknown_versions_are_refused() {
+        let newer = SaveFile::upgrade(SAVE_VERSION + 1, value_with_version(SAVE_VERSION + 1));
+        assert_eq!(newer.err().unwrap().to_string(),
+                   format!("the save is from a newer version ({}) of the game", SAVE_VERSION + 1));
+        let older = SaveFile::upgrade(0, value_with_version(0));
+        assert_eq!(older.err().unwrap().to_string(), "the save's version (0) is not recognised");
+    }
+
+    #[test]
+    fn incomplete_saves_are_refused() {
+        let mut value = value_with_version(SAVE_VERSION);
+        value.as_object_mut().unwrap().remove("path");
+        assert!(SaveFile::upgrade(SAVE_VERSION, value).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+use std::f64::consts::PI;
+use std::path::Path;
+
+use image::{ImageResult, Rgba, RgbaImage};
+use rand::prelude::*;
+
+use crate::body_plan_generator::{Appendage, SensoryOrgan};
+use crate::organism_generator::{EnergySource, Organism, Symmetry};
+
+const CANVAS_SIZE: u32 = 256; // in pixels, square
+const CENTRE: f64 = CANVAS_SIZE as f64 / 2.0;
+const BODY_RADIUS: f64 = 40.0; // in pixels; every body is drawn at the same scale
+const BILATERAL_BODY_LENGTH: f64 = 120.0;
+const LIMB_WIDTH: f64 = 5.0;
+const FINE_WIDTH: f64 = 2.0; // for cilia, whiskers and antennae
+const MAXIMUM_DRAWN_CILIA: u8 = 48; // more than this just fills the outline in
+const MAXIMUM_DRAWN_EYES: u8 = 8;
+const WAVE_SAMPLES: usize = 24;
+const PHOTOTROPH_COLOR: Rgba<u8> = Rgba([46, 125, 50, 255]);
+const CHEMOTROPH_COLOR: Rgba<u8> = Rgba([176, 90, 30, 255]);
+const HETEROTROPH_COLOR: Rgba<u8> = Rgba([45, 45, 60, 255]);
+const CLEAR: Rgba<u8> = Rgba([0, 0, 0, 0]);
+const NAME_HASH_OFFSET: u64 = 0xcbf29ce484222325; // FNV-1a
+const NAME_HASH_PRIME: u64 = 0x100000001b3;
+
+// A flat picture of an organism, built from its body plan on the CPU with no graphics device.
+// Bilateral bodies are seen from the side facing right, everything else from above.
+pub struct Silhouette {
+    image: RgbaImage,
+    color: Rgba<u8>,
+}
+
+impl Silhouette {
+    pub fn new(organism: &Organism) -> Silhouette {
+        let color = match organism.get_energy_source() {
+            EnergySource::Phototroph => PHOTOTROPH_COLOR,
+            EnergySource::Chemotroph => CHEMOTROPH_COLOR,
+            EnergySource::Heterotroph => HETEROTROPH_COLOR,
+        };
+        let mut silhouette = Silhouette { image: RgbaImage::new(CANVAS_SIZE, CANVAS_SIZE), color };
+
+        // Irregular shapes are seeded from the name, so a species always looks the same
+        let seed = organism.get_name().bytes()
+            .fold(NAME_HASH_OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(NAME_HASH_PRIME));
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        match organism.get_symmetry() {
+            Symmetry::Bilateral => silhouette.draw_bilateral(organism),
+            Symmetry::Radial => silhouette.draw_radial(organism),
+            Symmetry::Spherical => silhouette.draw_spherical(organism, &mut rng),
+            Symmetry::Asymmetrical => silhouette.draw_asymmetrical(organism, &mut rng),
+        }
+        silhouette
+    }
+
+    fn draw_bilateral(&mut self, organism: &Organism) {
+        let body_plan = organism.get_body_plan();
+        let segments = body_plan.get_segments() as f64;
+        let tail = CENTRE - BILATERAL_BODY_LENGTH / 2.0;
+        let segment_length = BILATERAL_BODY_LENGTH / segments;
+        let thickness = BODY_RADIUS * 0.6;
+        let head = (CENTRE + BILATERAL_BODY_LENGTH / 2.0, CENTRE);
+        let head_radius = thickness * 0.9;
+
+        // Attachment points along the underside, one per segment
+        let anchor = |i: f64, count: f64| tail + segment_length / 2.0
+            + (BILATERAL_BODY_LENGTH - segment_length) * if count > 1.0 { i / (count - 1.0) } else { 0.5 };
+
+        for (appendage, count) in body_plan.get_appendages() {
+            let pairs = (*count as f64 / 2.0).ceil().max(1.0);
+            for i in 0..pairs as usize {
+                let x = anchor(i as f64, pairs);
+                match appendage {
+                    Appendage::Legs => {
+                        self.draw_line((x, CENTRE), (x - 12.0, CENTRE + thickness + 40.0), LIMB_WIDTH);
+                        self.draw_line((x + 6.0, CENTRE), (x + 16.0, CENTRE + thickness + 38.0), LIMB_WIDTH);
+                    }
+                    Appendage::Wings => {
+                        let root = CENTRE - thickness * 0.5;
+                        self.fill_triangle((x - 18.0, root), (x + 18.0, root), (x - 40.0, root - 90.0));
+                    }
+                    Appendage::Fins => {
+                        self.fill_triangle((x - 12.0, CENTRE), (x + 12.0, CENTRE), (x - 24.0, CENTRE - thickness - 24.0));
+                        self.fill_triangle((x - 12.0, CENTRE), (x + 12.0, CENTRE), (x - 24.0, CENTRE + thickness + 24.0));
+                    }
+                    Appendage::Fronds => {
+                        self.draw_line((x, CENTRE), (x - 20.0, CENTRE - thickness - 60.0), LIMB_WIDTH * 2.0);
+                    }
+                    Appendage::Claws => {
+                        let wrist = (head.0 + head_radius + 18.0, CENTRE + head_radius);
+                        self.draw_line((head.0, CENTRE + head_radius * 0.5), wrist, LIMB_WIDTH);
+                        self.fill_triangle((wrist.0 - 4.0, wrist.1 - 6.0), (wrist.0 - 4.0, wrist.1 + 6.0), (wrist.0 + 16.0, wrist.1 - 8.0));
+                        break;
+                    }
+                    Appendage::Tentacles => {
+                        let start = (head.0, CENTRE + head_radius * 0.5);
+                        self.draw_wave(start, (start.0 + 10.0 - 10.0 * i as f64, CENTRE + 100.0), 6.0, LIMB_WIDTH);
+                    }
+                    Appendage::Flagella | Appendage::Cilia => {
+                        self.draw_wave((tail, CENTRE), (tail - 90.0, CENTRE), 8.0, FINE_WIDTH);
+                    }
+                }
+            }
+        }
+
+        for i in 0..segments as usize {
+            let x = tail + segment_length * (i as f64 + 0.5);
+            self.fill_ellipse((x, CENTRE), segment_length * 0.65, thickness);
+        }
+        self.fill_circle(head, head_radius);
+
+        for (organ, count) in body_plan.get_sensory_organs() {
+            match organ {
+                // Only one eye of each pair faces the viewer
+                SensoryOrgan::Eyes | SensoryOrgan::Eyespots => {
+                    let shown = (*count / 2).clamp(1, MAXIMUM_DRAWN_EYES);
+                    for i in 0..shown {
+                        let offset = (i as f64 - (shown - 1) as f64 / 2.0) * head_radius * 0.45;
+                        self.clear_circle((head.0 + head_radius * 0.35, CENTRE - head_radius * 0.3 + offset), head_radius * 0.18);
+                    }
+                }
+                SensoryOrgan::Antennae => {
+                    let base = (head.0 + head_radius * 0.3, CENTRE - head_radius * 0.8);
+                    self.draw_line(base, (base.0 + 30.0, base.1 - 50.0), FINE_WIDTH);
+                    self.draw_line(base, (base.0 + 45.0, base.1 - 38.0), FINE_WIDTH);
+                }
+                SensoryOrgan::Whiskers => {
+                    let base = (head.0 + head_radius * 0.8, CENTRE + head_radius * 0.2);
+                    for i in 0..(*count / 2).min(6) {
+                        let angle = -0.4 + 0.16 * i as f64;
+                        self.draw_line(base, (base.0 + 40.0 * angle.cos(), base.1 + 40.0 * angle.sin()), 1.0);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn draw_radial(&mut self, organism: &Organism) {
+        let body_plan = organism.get_body_plan();
+        for (appendage, count) in body_plan.get_appendages() {
+            for i in 0..*count {
+                let angle = 2.0 * PI * i as f64 / *count as f64;
+                let direction = (angle.cos(), angle.sin());
+                let start = (CENTRE + direction.0 * BODY_RADIUS * 0.8, CENTRE + direction.1 * BODY_RADIUS * 0.8);
+                let end = |length: f64| (CENTRE + direction.0 * length, CENTRE + direction.1 * length);
+                match appendage {
+                    Appendage::Tentacles | Appendage::Flagella => self.draw_wave(start, end(120.0), 5.0, LIMB_WIDTH),
+                    Appendage::Fronds => self.draw_line(start, end(110.0), LIMB_WIDTH * 2.5),
+                    Appendage::Wings => {
+                        let side = (-direction.1 * 20.0, direction.0 * 20.0);
+                        self.fill_triangle((start.0 + side.0, start.1 + side.1), (start.0 - side.0, start.1 - side.1), end(120.0));
+                    }
+                    _ => self.draw_line(start, end(100.0), LIMB_WIDTH),
+                }
+            }
+        }
+
+        self.fill_circle((CENTRE, CENTRE), BODY_RADIUS);
+        self.draw_eye_ring(organism, BODY_RADIUS * 0.6);
+    }
+
+    fn draw_spherical(&mut self, organism: &Organism, rng: &mut impl Rng) {
+        let radius = BODY_RADIUS * 1.5;
+        self.draw_fringe(organism, (CENTRE, CENTRE), radius, rng);
+        self.fill_circle((CENTRE, CENTRE), radius);
+        self.draw_eye_ring(organism, radius * 0.5);
+    }
+
+    // A lumpy cluster of overlapping lobes, with limbs sprouting from wherever the lobes end
+    fn draw_asymmetrical(&mut self, organism: &Organism, rng: &mut impl Rng) {
+        let mut lobes = vec![((CENTRE, CENTRE), BODY_RADIUS)];
+        for _ in 0..rng.gen_range(3..=7) {
+            let angle = rng.gen_range(0.0..2.0 * PI);
+            let distance = rng.gen_range(0.3..0.9) * BODY_RADIUS;
+            lobes.push(((CENTRE + distance * angle.cos(), CENTRE + distance * angle.sin()),
+                        rng.gen_range(0.4..0.8) * BODY_RADIUS));
+        }
+
+        let body_plan = organism.get_body_plan();
+        for (appendage, count) in body_plan.get_appendages() {
+            if *appendage == Appendage::Cilia {
+                continue;
+            }
+            for _ in 0..*count {
+                let (centre, radius) = lobes[rng.gen_range(0..lobes.len())];
+                let angle = rng.gen_range(0.0..2.0 * PI);
+                let length = rng.gen_range(60.0..110.0);
+                let end = (centre.0 + (radius + length) * angle.cos(), centre.1 + (radius + length) * angle.sin());
+                match appendage {
+                    Appendage::Fronds => self.draw_line(centre, end, LIMB_WIDTH * 2.0),
+                    Appendage::Flagella | Appendage::Tentacles => self.draw_wave(centre, end, 6.0, LIMB_WIDTH),
+                    _ => self.draw_line(centre, end, LIMB_WIDTH),
+                }
+            }
+        }
+
+        self.draw_fringe(organism, (CENTRE, CENTRE), BODY_RADIUS * 1.3, rng);
+        for (centre, radius) in lobes {
+            self.fill_circle(centre, radius);
+        }
+        self.draw_eye_ring(organism, BODY_RADIUS * 0.4);
+    }
+
+    // Cilia bristle out all around the body
+    fn draw_fringe(&mut self, organism: &Organism, centre: (f64, f64), radius: f64, rng: &mut impl Rng) {
+        for (appendage, count) in organism.get_body_plan().get_appendages() {
+            match appendage {
+                Appendage::Cilia => {
+                    let drawn = (*count).min(MAXIMUM_DRAWN_CILIA);
+                    for i in 0..drawn {
+                        let angle = 2.0 * PI * i as f64 / drawn as f64;
+                        let length = radius + 14.0;
+                        self.draw_line(centre, (centre.0 + length * angle.cos(), centre.1 + length * angle.sin()), FINE_WIDTH);
+                    }
+                }
+                Appendage::Flagella if *organism.get_symmetry() == Symmetry::Spherical => {
+                    for _ in 0..*count {
+                        let angle = rng.gen_range(0.0..2.0 * PI);
+                        let end = (centre.0 + (radius + 70.0) * angle.cos(), centre.1 + (radius + 70.0) * angle.sin());
+                        self.draw_wave(centre, end, 6.0, FINE_WIDTH * 1.5);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Eyes seen from above sit in a ring around the middle of the body
+    fn draw_eye_ring(&mut self, organism: &Organism, distance: f64) {
+        for (organ, count) in organism.get_body_plan().get_sensory_organs() {
+            if *organ != SensoryOrgan::Eyes && *organ != SensoryOrgan::Eyespots {
+                continue;
+            }
+            let shown = (*count).min(MAXIMUM_DRAWN_EYES);
+            for i in 0..shown {
+                let angle = 2.0 * PI * i as f64 / shown as f64;
+                let distance = if shown == 1 { 0.0 } else { distance };
+                self.clear_circle((CENTRE + distance * angle.cos(), CENTRE + distance * angle.sin()), 5.0);
+            }
+        }
+    }
+
+    // Each shape is rasterised by testing the pixels in its bounding box
+    fn fill_where(&mut self, bounds: (f64, f64, f64, f64), color: Rgba<u8>, inside: impl Fn(f64, f64) -> bool) {
+        let clamp = |value: f64| value.clamp(0.0, CANVAS_SIZE as f64 - 1.0) as u32;
+        for y in clamp(bounds.1.floor())..=clamp(bounds.3.ceil()) {
+            for x in clamp(bounds.0.floor())..=clamp(bounds.2.ceil()) {
+                if inside(x as f64 + 0.5, y as f64 + 0.5) {
+                    self.image.put_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
+    fn fill_circle(&mut self, centre: (f64, f64), radius: f64) {
+        self.fill_ellipse(centre, radius, radius);
+    }
+
+    fn clear_circle(&mut self, centre: (f64, f64), radius: f64) {
+        let bounds = (centre.0 - radius, centre.1 - radius, centre.0 + radius, centre.1 + radius);
+        self.fill_where(bounds, CLEAR, |x, y| (x - centre.0).powi(2) + (y - centre.1).powi(2) <= radius * radius);
+    }
+
+    fn fill_ellipse(&mut self, centre: (f64, f64), radius_x: f64, radius_y: f64) {
+        let bounds = (centre.0 - radius_x, centre.1 - radius_y, centre.0 + radius_x, centre.1 + radius_y);
+        self.fill_where(bounds, self.color, |x, y| {
+            ((x - centre.0) / radius_x).powi(2) + ((y - centre.1) / radius_y).powi(2) <= 1.0
+        });
+    }
+
+    fn fill_triangle(&mut self, a: (f64, f64), b: (f64, f64), c: (f64, f64)) {
+        let edge = |p: (f64, f64), q: (f64, f64), x: f64, y: f64| (q.0 - p.0) * (y - p.1) - (q.1 - p.1) * (x - p.0);
+        let bounds = (a.0.min(b.0).min(c.0), a.1.min(b.1).min(c.1), a.0.max(b.0).max(c.0), a.1.max(b.1).max(c.1));
+        self.fill_where(bounds, self.color, |x, y| {
+            let sides = [edge(a, b, x, y), edge(b, c, x, y), edge(c, a, x, y)];
+            sides.iter().all(|&side| side >= 0.0) || sides.iter().all(|&side| side <= 0.0)
+        });
+    }
+
+    // A line is every pixel within half its width of the segment between its ends
+    fn draw_line(&mut self, start: (f64, f64), end: (f64, f64), width: f64) {
+        let half = width / 2.0;
+        let bounds = (start.0.min(end.0) - half, start.1.min(end.1) - half,
+                      start.0.max(end.0) + half, start.1.max(end.1) + half);
+        let length_squared = (end.0 - start.0).powi(2) + (end.1 - start.1).powi(2);
+        self.fill_where(bounds, self.color, |x, y| {
+            let t = if length_squared == 0.0 {
+                0.0
+            } else {
+                (((x - start.0) * (end.0 - start.0) + (y - start.1) * (end.1 - start.1)) / length_squared).clamp(0.0, 1.0)
+            };
+            let nearest = (start.0 + t * (end.0 - start.0), start.1 + t * (end.1 - start.1));
+            (x - nearest.0).powi(2) + (y - nearest.1).powi(2) <= half * half
+        });
+    }
+
+    // A sinuous line for whip-like limbs, swinging from side to side along its length
+    fn draw_wave(&mut self, start: (f64, f64), end: (f64, f64), amplitude: f64, width: f64) {
+        let length = ((end.0 - start.0).powi(2) + (end.1 - start.1).powi(2)).sqrt().max(1.0);
+        let normal = (-(end.1 - start.1) / length, (end.0 - start.0) / length);
+        let point = |i: usize| {
+            let t = i as f64 / WAVE_SAMPLES as f64;
+            let swing = amplitude * (t * 3.0 * PI).sin() * t;
+            (start.0 + t * (end.0 - start.0) + normal.0 * swing, start.1 + t * (end.1 - start.1) + normal.1 * swing)
+        };
+        for i in 0..WAVE_SAMPLES {
+            self.draw_line(point(i), point(i + 1), width);
+        }
+    }
+
+    pub fn save_png(&self, path: &Path) -> ImageResult<()> {
+        self.image.save_with_format(path, image::ImageFormat::Png)
+    }
+}
@@ -5,6 +5,9 @@ use rand_derive2::RandGen;
 use std::collections::HashMap;
 use strum_macros::Display;
 
+use crate::galaxy_generator::GalaxyShape;
+use crate::name_generator;
+
 // In millions of years
 const LOW_MASS_STAR_AGE_MINIMUM: u16 = 10;
 const HIGH_MASS_STAR_AGE_MINIMUM: u16 = 1;
@@ -59,6 +62,40 @@ const WHITE_DWARF_STAR_ABUNDANCE: f64 = 0.05;
 const GIANT_STAR_ABUNDANCE: f64 = 0.004;
 const SUPER_GIANT_STAR_ABUNDANCE: f64 = 0.0001;
 
+// Stellar evolution; luminosities are in L☉, radii in R☉, and times in millions of years
+const SOLAR_TEMPERATURE: f64 = 5772.0;
+const SOLAR_MAIN_SEQUENCE_LIFETIME: f64 = 10000.0;
+const MAIN_SEQUENCE_LIFETIME_EXPONENT: f64 = -2.5;
+const MASS_LUMINOSITY_EXPONENT: f64 = 3.5;
+const MASS_TEMPERATURE_EXPONENT: f64 = 0.505;
+const ZERO_AGE_LUMINOSITY_FACTOR: f64 = 0.75; // the young Sun was ~25% dimmer than today
+const MAIN_SEQUENCE_BRIGHTENING: f64 = 0.75; // fractional luminosity gained over the main sequence
+const EVOLVED_STAR_MAIN_SEQUENCE_FRACTION: f64 = 0.9; // caps progenitor lifetimes to fit the star's age
+const WHITE_DWARF_PROGENITOR_MASS: f64 = 2.0; // evolved from A Star
+const WHITE_DWARF_RADIUS: f64 = 0.012;
+const WHITE_DWARF_REFERENCE_LUMINOSITY: f64 = 0.001; // after 1 billion years of cooling
+const WHITE_DWARF_REFERENCE_COOLING_TIME: f64 = 1000.0;
+const WHITE_DWARF_COOLING_EXPONENT: f64 = -1.4; // Mestel cooling
+const WHITE_DWARF_MINIMUM_COOLING_TIME: f64 = 10.0;
+const GIANT_LUMINOSITY_PER_MASS: f64 = 100.0;
+const SUPER_GIANT_LUMINOSITY_PER_MASS: f64 = 5000.0;
+const GIANT_STAR_TEMPERATURE: u16 = 4500;
+const WHITE_DWARF_STAR_TEMPERATURE: u16 = 10000;
+
+// Conservative habitable zone boundaries, in stellar flux relative to Earth's
+const HABITABLE_ZONE_INNER_FLUX: f64 = 1.1;
+const HABITABLE_ZONE_OUTER_FLUX: f64 = 0.53;
+
+const NAME_SEED_OFFSET: u64 = 15485863; // keeps naming independent of the star's physical rolls
+const PROPER_NAME_CHANCE: f64 = 0.2; // most stars are known only by their catalog designation
+const POSITION_SEED_OFFSET: u64 = 32452843; // keeps a star's place independent of its physical rolls
+const GALAXY_RADIUS: f64 = 300.0; // in light years
+const SPIRAL_ARMS: u8 = 2;
+const SPIRAL_WINDING: f64 = 4.0; // in radians an arm turns from the centre to the rim
+const SPIRAL_ARM_SPREAD: f64 = 0.35; // in radians stars stray either side of an arm
+const ELLIPTICAL_AXIS_RATIO: f64 = 0.6; // of the short axis to the long one
+const RING_INNER_RADIUS: f64 = 0.7; // as a fraction of the galaxy's radius
+
 // TODO: Remove and redesign Giant and SuperGiant, as they aren't classes and are found in
 // every class.
 #[derive(Display, Hash, Eq, PartialEq, Clone)]
@@ -76,7 +113,7 @@ pub enum StarClass {
 }
 
 #[derive(Display, RandGen, Clone, Hash, Eq, PartialEq)]
-enum StarColor {
+pub enum StarColor {
     Red,
     Yellow,
     Orange,
@@ -86,11 +123,41 @@ enum StarColor {
 
 #[derive(Clone)]
 pub struct Star {
+    seed: u64,
+    designation: String,
+    proper_name: Option<String>,
+    position: (f64, f64), // in light years from the centre of the galaxy
     class: StarClass,
     color: StarColor,
     mass: f64,
     age: u16,
     temperature: u16,
+    main_sequence_lifetime: u16,
+    zero_age_luminosity: f64,
+    zero_age_temperature: f64,
+    reference_temperature: u16,
+    reference_luminosity: f64,
+}
+
+// A snapshot of a star's properties at some point in its history
+pub struct StellarState {
+    luminosity: f64,
+    temperature: u16,
+    radius: f64,
+}
+
+impl StellarState {
+    pub fn get_luminosity(&self) -> f64 {
+        self.luminosity
+    }
+
+    pub fn get_temperature(&self) -> u16 {
+        self.temperature
+    }
+
+    pub fn get_radius(&self) -> f64 {
+        self.radius
+    }
 }
 
 lazy_static! {
@@ -139,99 +206,330 @@ lazy_static! {
     ]);
 }
 
+// Each class with the share of stars generated in it, so generated stars can be checked against it
+pub fn get_class_abundances() -> &'static Vec<(StarClass, f64)> {
+    &STARS
+}
+
 impl Star {
-    pub fn new(star_seed: u64) -> Star {
+    pub fn new(star_seed: u64, shape: &GalaxyShape) -> Star {
         let mut rng = StdRng::seed_from_u64(star_seed);
         let random_index = Self::generate_weighted_random_number(&ABUNDANCE, &mut rng);
 
-        match STARS[random_index].0 {
-            StarClass::M => Star {
-                class: StarClass::M,
-                color: StarColor::Red,
-                mass: Self::generate_star_mass(M_STAR_MASS_MINIMUM, M_STAR_MASS_MAXIMUM, &mut rng),
-                age: rng.gen_range(LOW_MASS_STAR_AGE_MINIMUM..=M_STAR_AGE_MAXIMUM),
-                temperature: STAR_TEMPERATURES[&StarClass::M][rng.gen_range(0..=9)],
-            },
-            StarClass::K => Star {
-                class: StarClass::K,
-                color: StarColor::Orange,
-                mass: Self::generate_star_mass(K_STAR_MASS_MINIMUM, K_STAR_MASS_MAXIMUM, &mut rng),
-                age: rng.gen_range(LOW_MASS_STAR_AGE_MINIMUM..=K_STAR_AGE_MAXIMUM),
-                temperature: STAR_TEMPERATURES[&StarClass::K][rng.gen_range(0..=9)],
-            },
-            StarClass::G => Star {
-                class: StarClass::G,
-                color: StarColor::Yellow,
-                mass: Self::generate_star_mass(G_STAR_MASS_MINIMUM, G_STAR_MASS_MAXIMUM, &mut rng),
-                age: rng.gen_range(LOW_MASS_STAR_AGE_MINIMUM..=G_STAR_AGE_MAXIMUM),
-                temperature: STAR_TEMPERATURES[&StarClass::G][rng.gen_range(0..=9)],
-            },
-            StarClass::F => Star {
-                class: StarClass::F,
-                color: StarColor::White,
-                mass: Self::generate_star_mass(F_STAR_MASS_MINIMUM, F_STAR_MASS_MAXIMUM, &mut rng),
-                age: rng.gen_range(LOW_MASS_STAR_AGE_MINIMUM..=F_STAR_AGE_MAXIMUM),
-                temperature: STAR_TEMPERATURES[&StarClass::F][rng.gen_range(0..=9)],
-            },
-            StarClass::A => Star {
-                class: StarClass::A,
-                color: StarColor::White,
-                mass: Self::generate_star_mass(A_STAR_MASS_MINIMUM, A_STAR_MASS_MAXIMUM, &mut rng),
-                age: rng.gen_range(HIGH_MASS_STAR_AGE_MINIMUM..=A_STAR_AGE_MAXIMUM),
-                temperature: STAR_TEMPERATURES[&StarClass::A][rng.gen_range(0..=9)],
-            },
-            StarClass::B => Star {
-                class: StarClass::B,
-                color: StarColor::Blue,
-                mass: Self::generate_star_mass(B_STAR_MASS_MINIMUM, B_STAR_MASS_MAXIMUM, &mut rng),
-                age: rng.gen_range(HIGH_MASS_STAR_AGE_MINIMUM..=B_STAR_AGE_MAXIMUM),
-                temperature: STAR_TEMPERATURES[&StarClass::B][rng.gen_range(0..=9)],
-            },
-            StarClass::O => Star {
-                class: StarClass::O,
-                color: StarColor::Blue,
-                mass: Self::generate_star_mass(O_STAR_MASS_MINIMUM, O_STAR_MASS_MAXIMUM, &mut rng),
-                age: rng.gen_range(HIGH_MASS_STAR_AGE_MINIMUM..=O_STAR_AGE_MAXIMUM),
-                temperature: STAR_TEMPERATURES[&StarClass::O][rng.gen_range(0..=9)],
-            },
-            StarClass::WhiteDwarf => Star {
-                class: StarClass::WhiteDwarf,
-                color: StarColor::White,
-                mass: Self::generate_star_mass(
+        let (class, color, mass, age, temperature) = match STARS[random_index].0 {
+            StarClass::M => {
+                let mass = Self::generate_star_mass(M_STAR_MASS_MINIMUM, M_STAR_MASS_MAXIMUM, &mut rng);
+                (
+                    StarClass::M,
+                    StarColor::Red,
+                    mass,
+                    Self::generate_main_sequence_age(&StarClass::M, LOW_MASS_STAR_AGE_MINIMUM,
+                                                     M_STAR_AGE_MAXIMUM, mass, &mut rng),
+                    STAR_TEMPERATURES[&StarClass::M][rng.gen_range(0..=9)],
+                )
+            }
+            StarClass::K => {
+                let mass = Self::generate_star_mass(K_STAR_MASS_MINIMUM, K_STAR_MASS_MAXIMUM, &mut rng);
+                (
+                    StarClass::K,
+                    StarColor::Orange,
+                    mass,
+                    Self::generate_main_sequence_age(&StarClass::K, LOW_MASS_STAR_AGE_MINIMUM,
+                                                     K_STAR_AGE_MAXIMUM, mass, &mut rng),
+                    STAR_TEMPERATURES[&StarClass::K][rng.gen_range(0..=9)],
+                )
+            }
+            StarClass::G => {
+                let mass = Self::generate_star_mass(G_STAR_MASS_MINIMUM, G_STAR_MASS_MAXIMUM, &mut rng);
+                (
+                    StarClass::G,
+                    StarColor::Yellow,
+                    mass,
+                    Self::generate_main_sequence_age(&StarClass::G, LOW_MASS_STAR_AGE_MINIMUM,
+                                                     G_STAR_AGE_MAXIMUM, mass, &mut rng),
+                    STAR_TEMPERATURES[&StarClass::G][rng.gen_range(0..=9)],
+                )
+            }
+            StarClass::F => {
+                let mass = Self::generate_star_mass(F_STAR_MASS_MINIMUM, F_STAR_MASS_MAXIMUM, &mut rng);
+                (
+                    StarClass::F,
+                    StarColor::White,
+                    mass,
+                    Self::generate_main_sequence_age(&StarClass::F, LOW_MASS_STAR_AGE_MINIMUM,
+                                                     F_STAR_AGE_MAXIMUM, mass, &mut rng),
+                    STAR_TEMPERATURES[&StarClass::F][rng.gen_range(0..=9)],
+                )
+            }
+            StarClass::A => {
+                let mass = Self::generate_star_mass(A_STAR_MASS_MINIMUM, A_STAR_MASS_MAXIMUM, &mut rng);
+                (
+                    StarClass::A,
+                    StarColor::White,
+                    mass,
+                    Self::generate_main_sequence_age(&StarClass::A, HIGH_MASS_STAR_AGE_MINIMUM,
+                                                     A_STAR_AGE_MAXIMUM, mass, &mut rng),
+                    STAR_TEMPERATURES[&StarClass::A][rng.gen_range(0..=9)],
+                )
+            }
+            StarClass::B => {
+                let mass = Self::generate_star_mass(B_STAR_MASS_MINIMUM, B_STAR_MASS_MAXIMUM, &mut rng);
+                (
+                    StarClass::B,
+                    StarColor::Blue,
+                    mass,
+                    Self::generate_main_sequence_age(&StarClass::B, HIGH_MASS_STAR_AGE_MINIMUM,
+                                                     B_STAR_AGE_MAXIMUM, mass, &mut rng),
+                    STAR_TEMPERATURES[&StarClass::B][rng.gen_range(0..=9)],
+                )
+            }
+            StarClass::O => {
+                let mass = Self::generate_star_mass(O_STAR_MASS_MINIMUM, O_STAR_MASS_MAXIMUM, &mut rng);
+                (
+                    StarClass::O,
+                    StarColor::Blue,
+                    mass,
+                    Self::generate_main_sequence_age(&StarClass::O, HIGH_MASS_STAR_AGE_MINIMUM,
+                                                     O_STAR_AGE_MAXIMUM, mass, &mut rng),
+                    STAR_TEMPERATURES[&StarClass::O][rng.gen_range(0..=9)],
+                )
+            }
+            StarClass::WhiteDwarf => (
+                StarClass::WhiteDwarf,
+                StarColor::White,
+                Self::generate_star_mass(
                     WHITE_DWARF_STAR_MASS_MINIMUM,
                     WHITE_DWARF_STAR_MASS_MAXIMUM,
                     &mut rng,
                 ),
-                age: rng.gen_range(WHITE_DWARF_STAR_AGE_MINIMUM..=WHITE_DWARF_STAR_AGE_MAXIMUM),
-                temperature: 10000,
-            },
-            StarClass::Giant => Star {
-                class: StarClass::Giant,
-                color: random(),
-                mass: Self::generate_star_mass(
+                rng.gen_range(WHITE_DWARF_STAR_AGE_MINIMUM..=WHITE_DWARF_STAR_AGE_MAXIMUM),
+                WHITE_DWARF_STAR_TEMPERATURE,
+            ),
+            StarClass::Giant => (
+                StarClass::Giant,
+                random(),
+                Self::generate_star_mass(
                     GIANT_STAR_MASS_MINIMUM,
                     GIANT_STAR_MASS_MAXIMUM,
                     &mut rng,
                 ),
-                age: rng.gen_range(GIANT_STAR_AGE_MINIMUM..=GIANT_STAR_AGE_MAXIMUM),
-                temperature: 4500,
-            },
-            StarClass::SuperGiant => Star {
-                class: StarClass::SuperGiant,
-                color: random(),
-                mass: Self::generate_star_mass(
+                rng.gen_range(GIANT_STAR_AGE_MINIMUM..=GIANT_STAR_AGE_MAXIMUM),
+                GIANT_STAR_TEMPERATURE,
+            ),
+            StarClass::SuperGiant => (
+                StarClass::SuperGiant,
+                random(),
+                Self::generate_star_mass(
                     SUPER_GIANT_STAR_MASS_MINIMUM,
                     SUPER_GIANT_STAR_MASS_MAXIMUM,
                     &mut rng,
                 ),
-                age: rng.gen_range(SUPER_GIANT_STAR_AGE_MINIMUM..=SUPER_GIANT_STAR_AGE_MAXIMUM),
-                temperature: STAR_TEMPERATURES[&StarClass::B][rng.gen_range(0..=9)],
+                rng.gen_range(SUPER_GIANT_STAR_AGE_MINIMUM..=SUPER_GIANT_STAR_AGE_MAXIMUM),
+                STAR_TEMPERATURES[&StarClass::B][rng.gen_range(0..=9)],
                 //Although supergiants exist in every class from O to M, the majority are
                 // spectral type B, more than at all other spectral classes combined.
-            },
+            ),
+        };
+
+        let main_sequence_lifetime = Self::calculate_main_sequence_lifetime(&class, mass, age);
+        let progenitor_mass = Self::calculate_progenitor_mass(&class, mass);
+        let zero_age_luminosity =
+            ZERO_AGE_LUMINOSITY_FACTOR * progenitor_mass.powf(MASS_LUMINOSITY_EXPONENT);
+        let zero_age_temperature = match class {
+            StarClass::WhiteDwarf | StarClass::Giant | StarClass::SuperGiant => {
+                SOLAR_TEMPERATURE * progenitor_mass.powf(MASS_TEMPERATURE_EXPONENT)
+            }
+            _ => {
+                let fraction = age as f64 / main_sequence_lifetime as f64;
+                temperature as f64 / Self::main_sequence_brightening(fraction).powf(0.125)
+            }
+        };
+
+        let mut name_rng = StdRng::seed_from_u64(star_seed.wrapping_add(NAME_SEED_OFFSET));
+        let proper_name = if name_rng.gen_bool(PROPER_NAME_CHANCE) {
+            Some(name_generator::generate_proper_name(name_rng.gen()))
+        } else {
+            None
+        };
+
+        let mut star = Star {
+            seed: star_seed,
+            designation: name_generator::generate_catalog_designation(star_seed),
+            proper_name,
+            position: Self::calculate_position(star_seed, shape),
+            class,
+            color,
+            mass,
+            age,
+            temperature,
+            main_sequence_lifetime,
+            zero_age_luminosity,
+            zero_age_temperature,
+            reference_temperature: temperature,
+            reference_luminosity: 0.0,
+        };
+        // The star starts out as it would be after advancing by no time at all; a white dwarf's
+        // temperature follows from how long it has been cooling
+        star.temperature = star.get_state_at(age).get_temperature();
+        star.reference_temperature = star.temperature;
+        star.reference_luminosity = star.get_luminosity();
+        star
+    }
+
+    // Ages the star, moving it off the main sequence once its hydrogen is exhausted
+    pub fn advance(&mut self, time: u16) {
+        self.age = self.age.saturating_add(time);
+
+        if self.age > self.main_sequence_lifetime {
+            match self.class {
+                StarClass::O | StarClass::B => {
+                    self.class = StarClass::SuperGiant;
+                }
+                StarClass::A | StarClass::F | StarClass::G | StarClass::K | StarClass::M => {
+                    self.class = StarClass::Giant;
+                    self.color = StarColor::Red;
+                    self.temperature = GIANT_STAR_TEMPERATURE;
+                }
+                _ => {}
+            }
+        }
+
+        self.temperature = self.get_state_at(self.age).get_temperature();
+    }
+
+    // Where in the galaxy the star lies, from its shape
+    fn calculate_position(star_seed: u64, shape: &GalaxyShape) -> (f64, f64) {
+        let mut rng = StdRng::seed_from_u64(star_seed.wrapping_add(POSITION_SEED_OFFSET));
+        match shape {
+            // Spread evenly over a flat disc
+            GalaxyShape::Disc => {
+                let radius = GALAXY_RADIUS * rng.gen_range(0.0..1.0_f64).sqrt();
+                let angle = rng.gen_range(0.0..std::f64::consts::TAU);
+                (radius * angle.cos(), radius * angle.sin())
+            }
+            // Strung along arms that wind outward from the centre
+            GalaxyShape::Spiral => {
+                let fraction = rng.gen_range(0.0..1.0_f64).sqrt();
+                let arm = rng.gen_range(0..SPIRAL_ARMS) as f64;
+                let angle = arm * std::f64::consts::TAU / SPIRAL_ARMS as f64 + fraction * SPIRAL_WINDING
+                    + rng.gen_range(-SPIRAL_ARM_SPREAD..SPIRAL_ARM_SPREAD);
+                (GALAXY_RADIUS * fraction * angle.cos(), GALAXY_RADIUS * fraction * angle.sin())
+            }
+            // Crowded toward the centre of a flattened oval
+            GalaxyShape::Elliptical => {
+                let radius = GALAXY_RADIUS * rng.gen_range(0.0..1.0_f64);
+                let angle = rng.gen_range(0.0..std::f64::consts::TAU);
+                (radius * angle.cos(), radius * angle.sin() * ELLIPTICAL_AXIS_RATIO)
+            }
+            // Spread evenly over a band around an empty centre
+            GalaxyShape::Ring => {
+                let radius = GALAXY_RADIUS * rng.gen_range(RING_INNER_RADIUS.powi(2)..1.0_f64).sqrt();
+                let angle = rng.gen_range(0.0..std::f64::consts::TAU);
+                (radius * angle.cos(), radius * angle.sin())
+            }
+        }
+    }
+
+    pub fn get_distance_to(&self, other: &Star) -> f64 {
+        ((self.position.0 - other.position.0).powi(2) + (self.position.1 - other.position.1).powi(2)).sqrt()
+    }
+
+    fn calculate_progenitor_mass(class: &StarClass, mass: f64) -> f64 {
+        match class {
+            StarClass::WhiteDwarf => WHITE_DWARF_PROGENITOR_MASS,
+            _ => mass,
+        }
+    }
+
+    // Evolved stars must have left the main sequence before reaching their current age
+    fn calculate_main_sequence_lifetime(class: &StarClass, mass: f64, age: u16) -> u16 {
+        let progenitor_mass = Self::calculate_progenitor_mass(class, mass);
+        let lifetime =
+            SOLAR_MAIN_SEQUENCE_LIFETIME * progenitor_mass.powf(MAIN_SEQUENCE_LIFETIME_EXPONENT);
+        let lifetime = match class {
+            StarClass::WhiteDwarf | StarClass::Giant | StarClass::SuperGiant => {
+                lifetime.min(age as f64 * EVOLVED_STAR_MAIN_SEQUENCE_FRACTION)
+            }
+            _ => lifetime,
+        };
+        lifetime.clamp(1.0, u16::MAX as f64) as u16
+    }
+
+    fn main_sequence_brightening(fraction: f64) -> f64 {
+        1.0 + MAIN_SEQUENCE_BRIGHTENING * fraction.min(1.0)
+    }
+
+    fn calculate_radius(luminosity: f64, temperature: f64) -> f64 {
+        luminosity.sqrt() * (SOLAR_TEMPERATURE / temperature).powi(2)
+    }
+
+    // Main sequence stars brighten slowly as they fuse hydrogen; evolved stars spent
+    // their early history on the main sequence before becoming what they are now.
+    pub fn get_state_at(&self, age: u16) -> StellarState {
+        let on_main_sequence = match self.class {
+            StarClass::WhiteDwarf | StarClass::Giant | StarClass::SuperGiant => {
+                age <= self.main_sequence_lifetime
+            }
+            _ => true,
+        };
+
+        if on_main_sequence {
+            let fraction = age as f64 / self.main_sequence_lifetime as f64;
+            let brightening = Self::main_sequence_brightening(fraction);
+            let luminosity = self.zero_age_luminosity * brightening;
+            let temperature = self.zero_age_temperature * brightening.powf(0.125);
+            return StellarState {
+                luminosity,
+                temperature: temperature.round() as u16,
+                radius: Self::calculate_radius(luminosity, temperature),
+            };
+        }
+
+        match self.class {
+            StarClass::WhiteDwarf => {
+                let cooling_time = ((age - self.main_sequence_lifetime) as f64)
+                    .max(WHITE_DWARF_MINIMUM_COOLING_TIME);
+                let luminosity = WHITE_DWARF_REFERENCE_LUMINOSITY
+                    * (cooling_time / WHITE_DWARF_REFERENCE_COOLING_TIME)
+                        .powf(WHITE_DWARF_COOLING_EXPONENT);
+                let temperature =
+                    SOLAR_TEMPERATURE * (luminosity / WHITE_DWARF_RADIUS.powi(2)).powf(0.25);
+                StellarState {
+                    luminosity,
+                    temperature: temperature.round() as u16,
+                    radius: WHITE_DWARF_RADIUS,
+                }
+            }
+            _ => {
+                let luminosity = match self.class {
+                    StarClass::Giant => GIANT_LUMINOSITY_PER_MASS * self.mass,
+                    _ => SUPER_GIANT_LUMINOSITY_PER_MASS * self.mass,
+                };
+                StellarState {
+                    luminosity,
+                    temperature: self.temperature,
+                    radius: Self::calculate_radius(luminosity, self.temperature as f64),
+                }
+            }
         }
     }
 
+    // The temperature that planetary heating is calculated from: the star's temperature when it
+    // was generated, scaled by how much its luminosity has changed since
+    pub fn get_heating_temperature_at(&self, age: u16) -> f64 {
+        let brightness = self.get_state_at(age).get_luminosity() / self.reference_luminosity;
+        self.reference_temperature as f64 * brightness.powf(0.25)
+    }
+
+    // Inner and outer edges in AU
+    pub fn get_habitable_zone_at(&self, age: u16) -> (f64, f64) {
+        let luminosity = self.get_state_at(age).get_luminosity();
+        (
+            (luminosity / HABITABLE_ZONE_INNER_FLUX).sqrt(),
+            (luminosity / HABITABLE_ZONE_OUTER_FLUX).sqrt(),
+        )
+    }
+
     fn generate_weighted_random_number(weights: &[f64], rng: &mut impl Rng) -> usize {
         let dist = WeightedIndex::new(weights).unwrap();
         dist.sample(rng)
@@ -241,17 +539,58 @@ impl Star {
         (rng.gen_range(min..=max) * 100.0).round() / 100.0
     }
 
+    // A main sequence star is never older than its hydrogen lasts, or it would already have left
+    // the main sequence before the game began, so its age is drawn from within its lifetime
+    fn generate_main_sequence_age(class: &StarClass, min: u16, max: u16, mass: f64, rng: &mut impl Rng) -> u16 {
+        let lifetime = Self::calculate_main_sequence_lifetime(class, mass, max);
+        rng.gen_range(min.min(lifetime)..=max.min(lifetime))
+    }
+
     pub fn get_info(&self) -> String{
+        let state = self.get_state_at(self.age);
+        let habitable_zone = self.get_habitable_zone_at(self.age);
         format!(
-            "There is an {} star that is {}, {} million years old, weighs {} M☉, and is {} K.",
+            "This is {}, {:.0} light years from the galactic centre. There is an {} star that is {}, {} million years old, weighs {} M☉, is {} K, \
+            has a radius of {:.3} R☉, and shines at {:.4} L☉. \
+            Its habitable zone lies between {:.2} and {:.2} AU.",
+            self.get_name(),
+            (self.position.0.powi(2) + self.position.1.powi(2)).sqrt(),
             &self.class,
             &self.color,
             &self.age,
             &self.mass,
             &self.temperature,
+            state.get_radius(),
+            state.get_luminosity(),
+            habitable_zone.0,
+            habitable_zone.1,
         )
     }
 
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_designation(&self) -> &String {
+        &self.designation
+    }
+
+    pub fn get_proper_name(&self) -> Option<&String> {
+        self.proper_name.as_ref()
+    }
+
+    pub fn get_position(&self) -> (f64, f64) {
+        self.position
+    }
+
+    // The catalog designation, along with the proper name if the star has one
+    pub fn get_name(&self) -> String {
+        match &self.proper_name {
+            Some(proper_name) => format!("{} ({})", self.designation, proper_name),
+            None => self.designation.clone(),
+        }
+    }
+
     pub fn get_class(&self) -> &StarClass {
         &self.class
     }
@@ -271,4 +610,37 @@ impl Star {
     pub fn get_temperature(&self) -> u16 {
         self.temperature
     }
+
+    pub fn get_luminosity(&self) -> f64 {
+        self.get_state_at(self.age).get_luminosity()
+    }
+
+    pub fn get_radius(&self) -> f64 {
+        self.get_state_at(self.age).get_radius()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn main_sequence_stars_are_younger_than_their_lifetimes() {
+        for seed in 0..20_000 {
+            let star = Star::new(seed, &GalaxyShape::Disc);
+            if !matches!(star.class, StarClass::WhiteDwarf | StarClass::Giant | StarClass::SuperGiant) {
+                assert!(star.age <= star.main_sequence_lifetime, "star {} outlived its hydrogen", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn advancing_by_no_time_changes_nothing() {
+        for seed in 0..20_000 {
+            let star = Star::new(seed, &GalaxyShape::Disc);
+            let mut advanced = star.clone();
+            advanced.advance(0);
+            assert_eq!(star.get_info(), advanced.get_info(), "star {} changed", seed);
+        }
+    }
 }
@@ -0,0 +1,441 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::rc::Rc;
+
+use slint::{Color, ComponentHandle, ModelRc, VecModel};
+
+use crate::game_manager::Game;
+use crate::planet_generator::{Planet, PlanetClass};
+use crate::star_generator::{StarClass, StarColor};
+use ui::{Dot, StarMap};
+
+const ZOOM_PER_KEY: f64 = 1.25;
+const ZOOM_MINIMUM: f64 = 0.5;
+const ZOOM_MAXIMUM: f64 = 40.0;
+const STAR_RADIUS: f64 = 3.0; // in pixels, for a main sequence star
+const PLANET_RADIUS: f64 = 5.0; // in pixels, for a rocky planet
+const ORBIT_SPACING_ANGLE: f64 = 2.39996; // in radians, the golden angle, so neighbouring planets never line up
+const PLAIN_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0]; // for a star of no particular colour
+
+// The generated code implements traits from inside functions, which newer compilers warn about
+#[allow(non_local_definitions)]
+mod ui {
+    slint::slint! {
+        // A star or planet, at map coordinates that run from -1 to 1 out to the farthest of them
+        export struct Dot {
+            index: int,
+            x: float,
+            y: float,
+            orbit: float,
+            radius: length,
+            color: color,
+            label: string,
+            selected: bool,
+        }
+
+        export component StarMap inherits Window {
+            title: root.caption;
+            preferred-width: 900px;
+            preferred-height: 900px;
+            background: #05050f;
+            forward-focus: keys;
+
+            in property <string> caption;
+            in property <[Dot]> dots;
+            in property <bool> orbits;
+            in property <color> star-color;
+            in property <length> offset-x;
+            in property <length> offset-y;
+            in property <float> zoom: 1;
+
+            callback picked(int);
+            callback pressed(bool);
+            callback dragged(length, length);
+            callback back();
+            callback close();
+            callback zoom-by(int);
+
+            // Pixels per unit of map coordinates, and where the map's centre has been dragged to
+            property <length> scale: (min(root.width, root.height) / 2 - 40px) * root.zoom;
+            property <length> centre-x: root.width / 2 + root.offset-x;
+            property <length> centre-y: root.height / 2 + root.offset-y;
+
+            keys := FocusScope {
+                key-pressed(event) => {
+                    if (event.text == Key.Backspace) {
+                        root.back();
+                        return accept;
+                    }
+                    if (event.text == Key.Escape) {
+                        root.close();
+                        return accept;
+                    }
+                    if (event.text == "+" || event.text == "=") {
+                        root.zoom-by(1);
+                        return accept;
+                    }
+                    if (event.text == "-") {
+                        root.zoom-by(-1);
+                        return accept;
+                    }
+                    return reject;
+                }
+
+                TouchArea {
+                    pointer-event(event) => {
+                        if (event.kind == PointerEventKind.down) {
+                            root.pressed(event.button == PointerEventButton.left);
+                        }
+                    }
+                    moved => {
+                        root.dragged(self.mouse-x - self.pressed-x, self.mouse-y - self.pressed-y);
+                    }
+                }
+            }
+
+            for dot in root.dots : Rectangle {
+                visible: root.orbits;
+                x: root.centre-x - dot.orbit * root.scale;
+                y: root.centre-y - dot.orbit * root.scale;
+                width: dot.orbit * root.scale * 2;
+                height: self.width;
+                border-radius: self.width / 2;
+                border-width: 1px;
+                border-color: #4c4c66;
+            }
+
+            if root.orbits : Rectangle {
+                x: root.centre-x - 12px;
+                y: root.centre-y - 12px;
+                width: 24px;
+                height: 24px;
+                border-radius: 12px;
+                background: root.star-color;
+            }
+
+            // Each dot sits in a larger square, so small ones are still easy to hover and click
+            for dot in root.dots : Rectangle {
+                x: root.centre-x + dot.x * root.scale - 10px;
+                y: root.centre-y + dot.y * root.scale - 10px;
+                width: 20px;
+                height: 20px;
+
+                Rectangle {
+                    x: (parent.width - self.width) / 2;
+                    y: (parent.height - self.height) / 2;
+                    width: dot.radius * 2;
+                    height: dot.radius * 2;
+                    border-radius: dot.radius;
+                    background: dot.color;
+                }
+                if dot.selected : Rectangle {
+                    x: (parent.width - self.width) / 2;
+                    y: (parent.height - self.height) / 2;
+                    width: dot.radius * 2 + 6px;
+                    height: self.width;
+                    border-radius: self.width / 2;
+                    border-width: 1px;
+                    border-color: white;
+                }
+                area := TouchArea {
+                    clicked => { root.picked(dot.index); }
+                }
+                if area.has-hover || dot.selected : Text {
+                    x: parent.width;
+                    y: (parent.height - self.height) / 2;
+                    text: dot.label;
+                    color: white;
+                }
+            }
+
+            Text {
+                x: 8px;
+                y: 8px;
+                text: root.caption;
+                color: white;
+                font-weight: 700;
+            }
+            Text {
+                x: 8px;
+                y: root.height - self.height - 8px;
+                text: "Click a star to open its system. Drag to pan, + and - to zoom, Backspace or right click to go back, and Esc to close.";
+                color: #8888aa;
+            }
+        }
+    }
+}
+
+// Where the map is looking: the pixel offset dragged so far and how far it is zoomed in
+struct Camera {
+    offset: [f64; 2],
+    zoom: f64,
+}
+
+impl Camera {
+    fn new() -> Camera {
+        Camera { offset: [0.0, 0.0], zoom: 1.0 }
+    }
+}
+
+// A star or planet drawn on the map, at its map coordinates before the camera is applied
+struct Marker {
+    index: usize,
+    position: [f64; 2],
+    radius: f64,
+    color: [f32; 4],
+    label: String,
+}
+
+// What the map is showing besides the game: the camera, the planet picked out, if any, and why
+// the last click went nowhere. Markers are laid out once per scene, as that means going through
+// every star.
+struct MapState {
+    camera: Camera,
+    drag_start: [f64; 2], // the camera offset when the current drag began
+    selected: Option<usize>,
+    status: Option<String>,
+    markers: Vec<Marker>,
+}
+
+// Opens a window onto the galaxy, or onto the system of the current star. The map steps in and
+// out of the same game the command line does, so closing it leaves the player wherever they
+// clicked to. A player who was below the star and only looked is put back where they were.
+pub fn run(game: &Rc<RefCell<Game>>) -> Result<(), Box<dyn Error>> {
+    if !has_display() {
+        return Err("there is no display to open a window on".into());
+    }
+    let below_star = game.borrow().get_path().len() > 1;
+    let ui = open(game)?;
+    let shown = game.borrow().get_path().clone();
+    ui.run()?;
+    if below_star && *game.borrow().get_path() == shown {
+        game.borrow_mut().back()?;
+    }
+    Ok(())
+}
+
+// The windowing library panics rather than failing when there is no display, so look first
+pub fn has_display() -> bool {
+    !cfg!(all(unix, not(target_os = "macos")))
+        || std::env::var_os("DISPLAY").is_some()
+        || std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+// Builds the map's window on whatever platform Slint runs on. Slint keeps a single event loop
+// for the whole process and lends it to every window, so the map and the inspector may be opened
+// as often as the player likes.
+pub fn open(game: &Rc<RefCell<Game>>) -> Result<StarMap, Box<dyn Error>> {
+    // Anything deeper than a star is shown as the system it belongs to
+    if game.borrow().get_path().len() > 1 {
+        let star = game.borrow().get_path()[..1].to_vec();
+        game.borrow_mut().go_to(&star)?;
+    }
+
+    let state = Rc::new(RefCell::new(MapState {
+        camera: Camera::new(),
+        drag_start: [0.0, 0.0],
+        selected: None,
+        status: None,
+        markers: layout(&game.borrow()),
+    }));
+    let ui = StarMap::new()?;
+    refresh(&ui, &game.borrow(), &state.borrow());
+
+    // Clicking a star opens its system; clicking a planet selects it
+    let (handle, shared_game, shared) = (ui.as_weak(), game.clone(), state.clone());
+    ui.on_picked(move |index| {
+        let (mut game, mut state) = (shared_game.borrow_mut(), shared.borrow_mut());
+        let index = index as usize;
+        if game.get_path().is_empty() {
+            match game.step_in(index) {
+                Ok(()) => navigated(&game, &mut state),
+                Err(error) => state.status = Some(error),
+            }
+        } else {
+            state.selected = Some(index);
+        }
+        refresh(&handle.unwrap(), &game, &state);
+    });
+
+    // The left button starts a drag; the right one steps back out
+    let (handle, shared_game, shared) = (ui.as_weak(), game.clone(), state.clone());
+    ui.on_pressed(move |left| {
+        let (mut game, mut state) = (shared_game.borrow_mut(), shared.borrow_mut());
+        if left {
+            state.drag_start = state.camera.offset;
+        } else if game.step_out().is_ok() {
+            navigated(&game, &mut state);
+            refresh(&handle.unwrap(), &game, &state);
+        }
+    });
+
+    let (handle, shared) = (ui.as_weak(), state.clone());
+    ui.on_dragged(move |x, y| {
+        let mut state = shared.borrow_mut();
+        state.camera.offset = [state.drag_start[0] + x as f64, state.drag_start[1] + y as f64];
+        place_camera(&handle.unwrap(), &state.camera);
+    });
+
+    // Zooms about the middle of the window, so whatever is there stays put
+    let (handle, shared) = (ui.as_weak(), state.clone());
+    ui.on_zoom_by(move |steps| {
+        let mut state = shared.borrow_mut();
+        let zoom = (state.camera.zoom * ZOOM_PER_KEY.powi(steps)).clamp(ZOOM_MINIMUM, ZOOM_MAXIMUM);
+        let ratio = zoom / state.camera.zoom;
+        state.camera.offset = [state.camera.offset[0] * ratio, state.camera.offset[1] * ratio];
+        state.camera.zoom = zoom;
+        place_camera(&handle.unwrap(), &state.camera);
+    });
+
+    let (handle, shared_game, shared) = (ui.as_weak(), game.clone(), state.clone());
+    ui.on_back(move || {
+        let (mut game, mut state) = (shared_game.borrow_mut(), shared.borrow_mut());
+        if game.step_out().is_ok() {
+            navigated(&game, &mut state);
+            refresh(&handle.unwrap(), &game, &state);
+        }
+    });
+
+    let handle = ui.as_weak();
+    ui.on_close(move || {
+        let _ = handle.unwrap().hide();
+    });
+
+    Ok(ui)
+}
+
+// A new scene starts with the camera back in the middle and nothing picked out
+fn navigated(game: &Game, state: &mut MapState) {
+    state.camera = Camera::new();
+    state.selected = None;
+    state.status = None;
+    state.markers = layout(game);
+}
+
+// Rebuilds the dots and the caption from the game, and puts the camera where it was left
+fn refresh(ui: &StarMap, game: &Game, state: &MapState) {
+    let dots: Vec<Dot> = state.markers.iter()
+        .map(|marker| Dot {
+            index: marker.index as i32,
+            x: marker.position[0] as f32,
+            y: marker.position[1] as f32,
+            orbit: marker.position[0].hypot(marker.position[1]) as f32,
+            radius: marker.radius as f32,
+            color: to_color(marker.color),
+            label: marker.label.as_str().into(),
+            selected: state.selected == Some(marker.index),
+        })
+        .collect();
+    let system = game.get_scene().get_planetary_system();
+
+    // The caption does the work of a status bar: where the player is, what is selected, and
+    // why the last click went nowhere
+    let mut caption = game.get_breadcrumbs();
+    if let Some(marker) = state.selected.and_then(|index| state.markers.iter().find(|marker| marker.index == index)) {
+        caption.push_str(&format!("  |  {}", marker.label));
+    }
+    if let Some(status) = &state.status {
+        caption.push_str(&format!("  |  {}", status));
+    }
+
+    ui.set_dots(ModelRc::new(VecModel::from(dots)));
+    ui.set_orbits(system.is_some());
+    ui.set_star_color(to_color(system.map_or(PLAIN_COLOR, |system| color_of(system.get_star().get_color()))));
+    ui.set_caption(caption.into());
+    place_camera(ui, &state.camera);
+}
+
+fn place_camera(ui: &StarMap, camera: &Camera) {
+    ui.set_offset_x(camera.offset[0] as f32);
+    ui.set_offset_y(camera.offset[1] as f32);
+    ui.set_zoom(camera.zoom as f32);
+}
+
+// Places every star or planet of the current scene: stars where they lie in the galaxy, or
+// planets on their orbits, spaced in proportion to their distance from the star. Either way
+// the farthest lies at 1, and the window scales that to fit.
+fn layout(game: &Game) -> Vec<Marker> {
+    let scene = game.get_scene();
+    let labels: Vec<String> = game.get_units().iter()
+        .map(|unit| format!("{} {}", unit.get_kind(), unit.get_name()))
+        .collect();
+    let label = |index: usize| labels.get(index).cloned().unwrap_or_default();
+    if let Some(stars) = scene.get_stars() {
+        let extent = stars.iter()
+            .map(|star| star.get_position().0.abs().max(star.get_position().1.abs()))
+            .fold(1.0, f64::max);
+        stars.iter().enumerate()
+            .map(|(index, star)| Marker {
+                index,
+                position: [star.get_position().0 / extent, star.get_position().1 / extent],
+                radius: star_radius(star.get_class()),
+                color: color_of(star.get_color()),
+                label: label(index),
+            })
+            .collect()
+    } else if let Some(system) = scene.get_planetary_system() {
+        let extent = system.get_planets().iter().map(|planet| planet.get_distance() as f64).fold(1.0, f64::max);
+        system.get_planets().iter().enumerate()
+            .map(|(index, planet)| {
+                let orbit = planet.get_distance() as f64 / extent;
+                let angle = index as f64 * ORBIT_SPACING_ANGLE;
+                Marker {
+                    index,
+                    position: [orbit * angle.cos(), orbit * angle.sin()],
+                    radius: planet_radius(planet.get_class()),
+                    color: planet_color(planet),
+                    label: label(index),
+                }
+            })
+            .collect()
+    } else {
+        Vec::new()
+    }
+}
+
+fn to_color(color: [f32; 4]) -> Color {
+    Color::from_argb_f32(color[3], color[0], color[1], color[2])
+}
+
+fn color_of(color: &StarColor) -> [f32; 4] {
+    match color {
+        StarColor::Red => [1.0, 0.45, 0.35, 1.0],
+        StarColor::Orange => [1.0, 0.7, 0.4, 1.0],
+        StarColor::Yellow => [1.0, 0.95, 0.6, 1.0],
+        StarColor::Blue => [0.6, 0.7, 1.0, 1.0],
+        StarColor::White => [0.95, 0.95, 1.0, 1.0],
+    }
+}
+
+fn star_radius(class: &StarClass) -> f64 {
+    match class {
+        StarClass::WhiteDwarf => STAR_RADIUS * 0.6,
+        StarClass::Giant => STAR_RADIUS * 2.0,
+        StarClass::SuperGiant | StarClass::O | StarClass::B => STAR_RADIUS * 2.5,
+        _ => STAR_RADIUS,
+    }
+}
+
+fn planet_radius(class: &PlanetClass) -> f64 {
+    match class {
+        PlanetClass::GasGiant => PLANET_RADIUS * 2.0,
+        PlanetClass::IceGiant => PLANET_RADIUS * 1.5,
+        PlanetClass::Dwarf => PLANET_RADIUS * 0.6,
+        PlanetClass::Rocky => PLANET_RADIUS,
+    }
+}
+
+// Living worlds stand out in green, and habitable ones in blue
+fn planet_color(planet: &Planet) -> [f32; 4] {
+    if planet.has_life() {
+        [0.3, 0.9, 0.4, 1.0]
+    } else if planet.get_habitability() {
+        [0.3, 0.6, 1.0, 1.0]
+    } else {
+        match planet.get_class() {
+            PlanetClass::GasGiant => [0.85, 0.7, 0.5, 1.0],
+            PlanetClass::IceGiant => [0.6, 0.85, 0.9, 1.0],
+            _ => [0.6, 0.55, 0.5, 1.0],
+        }
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::galaxy_generator::GalaxySettings;
+use crate::game_manager::{Property, Value};
+use crate::planetary_environment_generator::PlanetaryEnvironment;
+use crate::planetary_system_generator::PlanetarySystem;
+use crate::region_generator;
+use crate::star_generator;
+
+const DEVIATION_LIMIT: f64 = 3.0; // in standard errors; wider gaps from the expected share are flagged
+const PROGRESS_INTERVAL: u64 = 10_000; // stars between progress reports
+const ORGANISM_TRAITS: [&str; 14] = ["Size", "Organization", "Symmetry", "Structure", "Solvent", "Metabolism",
+    "Energy source", "Trophic role", "Habitat", "Reproduction", "Locomotion", "Senses", "Skeleton", "Covering"];
+
+// How often each value turned up
+#[derive(Default)]
+struct Tally {
+    counts: BTreeMap<String, u64>,
+    total: u64,
+}
+
+impl Tally {
+    fn add(&mut self, value: impl ToString) {
+        *self.counts.entry(value.to_string()).or_insert(0) += 1;
+        self.total += 1;
+    }
+
+    fn get_count(&self, value: &str) -> u64 {
+        self.counts.get(value).copied().unwrap_or(0)
+    }
+
+    fn get_fraction(&self, value: &str) -> f64 {
+        fraction(self.get_count(value), self.total)
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let values: serde_json::Map<String, serde_json::Value> = self.counts.iter()
+            .map(|(value, &count)| (value.clone(), json!({ "count": count, "fraction": fraction(count, self.total) })))
+            .collect();
+        serde_json::Value::Object(values)
+    }
+
+    fn get_info(&self) -> String {
+        self.counts.iter()
+            .map(|(value, &count)| format!("  {:<24}{:>9}{:>10.4}", value, count, fraction(count, self.total)))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+fn fraction(count: u64, total: u64) -> f64 {
+    if total == 0 { 0.0 } else { count as f64 / total as f64 }
+}
+
+// Distributions over a sample of the galaxy, as the game shows it when it begins, for checking
+// that the generators still produce what they were built to
+struct Stats {
+    galaxy: GalaxySettings,
+    star_classes: Tally,
+    planets_per_star: BTreeMap<usize, u64>,
+    classes_by_distance: BTreeMap<u8, Tally>, // in AU
+    habitable: u64, // planets
+    living: u64, // planets
+    oceans: Tally,
+    moon_compositions: Tally,
+    moon_mass: f64, // summed, for the mean
+    moon_orbital_radius: f64, // summed, in planetary radii
+    tidally_locked: u64, // moons
+    subsurface_oceans: u64, // moons
+    traits: BTreeMap<&'static str, Tally>,
+}
+
+impl Stats {
+    fn new(galaxy: &GalaxySettings) -> Stats {
+        Stats {
+            galaxy: galaxy.clone(),
+            star_classes: Tally::default(),
+            planets_per_star: BTreeMap::new(),
+            classes_by_distance: BTreeMap::new(),
+            habitable: 0,
+            living: 0,
+            oceans: Tally::default(),
+            moon_compositions: Tally::default(),
+            moon_mass: 0.0,
+            moon_orbital_radius: 0.0,
+            tidally_locked: 0,
+            subsurface_oceans: 0,
+            traits: ORGANISM_TRAITS.iter().map(|&name| (name, Tally::default())).collect(),
+        }
+    }
+
+    // Each system stands alone, as life moving between stars would need the whole galaxy. Only
+    // living planets have their biospheres built, since that is by far the slowest part.
+    fn add_star(&mut self, index: u64) {
+        let star = self.galaxy.create_star(index);
+        self.star_classes.add(star.get_class());
+
+        let planets = PlanetarySystem::generate_native_planets(&star);
+        *self.planets_per_star.entry(planets.len()).or_insert(0) += 1;
+        for planet in &planets {
+            self.classes_by_distance.entry(planet.get_distance()).or_default().add(planet.get_class());
+            self.oceans.add(planet.get_ocean());
+            if planet.get_habitability() {
+                self.habitable += 1;
+            }
+            for moon in PlanetaryEnvironment::generate_moons(planet) {
+                self.moon_compositions.add(moon.get_composition());
+                self.moon_mass += moon.get_mass() as f64;
+                self.moon_orbital_radius += moon.get_orbital_radius();
+                self.tidally_locked += moon.is_tidally_locked() as u64;
+                self.subsurface_oceans += moon.has_subsurface_ocean() as u64;
+            }
+            if !planet.has_life() {
+                continue;
+            }
+            self.living += 1;
+            for organism in PlanetaryEnvironment::new(planet).get_species() {
+                self.add_organism(&region_generator::describe_organism(organism));
+            }
+        }
+    }
+
+    // Senses are counted one by one, since an organism has several
+    fn add_organism(&mut self, properties: &[Property]) {
+        for property in properties {
+            let Some(tally) = self.traits.get_mut(property.get_name().as_str()) else {
+                continue;
+            };
+            match (property.get_name().as_str(), property.get_value()) {
+                ("Senses", Value::Text(senses)) => senses.split(", ")
+                    .filter(|sense| !sense.is_empty())
+                    .for_each(|sense| tally.add(sense)),
+                (_, value) => tally.add(value),
+            }
+        }
+    }
+
+    fn get_stars(&self) -> u64 {
+        self.star_classes.total
+    }
+
+    fn get_planets(&self) -> u64 {
+        self.planets_per_star.iter().map(|(&count, &stars)| count as u64 * stars).sum()
+    }
+
+    fn get_moons(&self) -> u64 {
+        self.moon_compositions.total
+    }
+
+    fn get_species(&self) -> u64 {
+        self.traits.get("Size").map_or(0, |tally| tally.total)
+    }
+
+    // Each class's share against the abundance it is generated with, and how many standard
+    // errors apart the two are
+    fn get_class_deviations(&self) -> Vec<(String, u64, f64, f64, f64)> {
+        let abundances = star_generator::get_class_abundances();
+        let total_abundance: f64 = abundances.iter().map(|(_, abundance)| abundance).sum();
+        let stars = self.get_stars().max(1) as f64;
+        abundances.iter()
+            .map(|(class, abundance)| {
+                let class = class.to_string();
+                let expected = abundance / total_abundance;
+                let observed = self.star_classes.get_fraction(&class);
+                let error = (expected * (1.0 - expected) / stars).sqrt();
+                let deviation = if error > 0.0 { (observed - expected) / error } else { 0.0 };
+                (class.clone(), self.star_classes.get_count(&class), observed, expected, deviation)
+            })
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let classes: serde_json::Map<String, serde_json::Value> = self.get_class_deviations().into_iter()
+            .map(|(class, count, observed, expected, deviation)| (class, json!({
+                "count": count,
+                "fraction": observed,
+                "expected": expected,
+                "deviation": deviation,
+                "flagged": deviation.abs() > DEVIATION_LIMIT,
+            })))
+            .collect();
+        let planets_per_star: serde_json::Map<String, serde_json::Value> = self.planets_per_star.iter()
+            .map(|(count, &stars)| (count.to_string(), json!(stars)))
+            .collect();
+        let classes_by_distance: serde_json::Map<String, serde_json::Value> = self.classes_by_distance.iter()
+            .map(|(distance, tally)| (distance.to_string(), tally.to_json()))
+            .collect();
+        let traits: serde_json::Map<String, serde_json::Value> = self.traits.iter()
+            .map(|(&name, tally)| (name.to_string(), tally.to_json()))
+            .collect();
+        json!({
+            "galaxy": {
+                "seed": self.galaxy.get_seed(),
+                "shape": self.galaxy.get_shape().to_string(),
+                "size": self.galaxy.get_size(),
+            },
+            "stars": self.get_stars(),
+            "planets": self.get_planets(),
+            "species": self.get_species(),
+            "star_classes": classes,
+            "planets_per_star": {
+                "mean": fraction(self.get_planets(), self.get_stars()),
+                "histogram": planets_per_star,
+            },
+            "planet_classes_by_distance": classes_by_distance,
+            "habitable_fraction": fraction(self.habitable, self.get_planets()),
+            "living_fraction": fraction(self.living, self.get_planets()),
+            "oceans": self.oceans.to_json(),
+            "moons": {
+                "per_planet": fraction(self.get_moons(), self.get_planets()),
+                "compositions": self.moon_compositions.to_json(),
+                "mean_mass": self.moon_mass / self.get_moons().max(1) as f64,
+                "mean_orbital_radius": self.moon_orbital_radius / self.get_moons().max(1) as f64,
+                "tidally_locked_fraction": fraction(self.tidally_locked, self.get_moons()),
+                "subsurface_ocean_fraction": fraction(self.subsurface_oceans, self.get_moons()),
+            },
+            "organism_traits": traits,
+        })
+    }
+
+    fn get_info(&self) -> String {
+        let mut s = format!("Sampled {} of the {} stars of the {} galaxy with seed {}: {} planets, {} of them \
+                             living, and {} species.\n", self.get_stars(), self.galaxy.get_size(),
+                            self.galaxy.get_shape(), self.galaxy.get_seed(), self.get_planets(), self.living,
+                            self.get_species());
+        s.push_str("Planets are each system's own, without life travelling between stars.\n");
+
+        s.push_str(&format!("\nStar classes{:>21}{:>10}{:>10}{:>11}\n", "count", "share", "expected", "deviation"));
+        for (class, count, observed, expected, deviation) in self.get_class_deviations() {
+            let flag = if deviation.abs() > DEVIATION_LIMIT { "  !" } else { "" };
+            s.push_str(&format!("  {:<24}{:>9}{:>10.4}{:>10.4}{:>11.2}{}\n",
+                                class, count, observed, expected, deviation, flag));
+        }
+
+        s.push_str(&format!("\nPlanets per star (mean {:.2})\n", fraction(self.get_planets(), self.get_stars())));
+        for (count, &stars) in &self.planets_per_star {
+            s.push_str(&format!("  {:<24}{:>9}{:>10.4}\n", count, stars, fraction(stars, self.get_stars())));
+        }
+
+        s.push_str("\nPlanet classes by distance\n");
+        for (distance, tally) in &self.classes_by_distance {
+            let shares: Vec<String> = tally.counts.keys()
+                .map(|class| format!("{} {:.3}", class, tally.get_fraction(class)))
+                .collect();
+            s.push_str(&format!("  {:>2} AU{:>9}  {}\n", distance, tally.total, shares.join(", ")));
+        }
+
+        s.push_str(&format!("\nHabitable planets: {:.4}\nLiving planets: {:.4}\n",
+                            fraction(self.habitable, self.get_planets()), fraction(self.living, self.get_planets())));
+        s.push_str(&format!("\nOceans\n{}\n", self.oceans.get_info()));
+        let moons = self.get_moons().max(1) as f64;
+        s.push_str(&format!("\nMoons (mean {:.2} per planet, mass {:.1}, orbital radius {:.1} planetary radii)\n{}\n\
+                             Tidally locked: {:.4}\nSubsurface oceans: {:.4}\n",
+                            fraction(self.get_moons(), self.get_planets()), self.moon_mass / moons,
+                            self.moon_orbital_radius / moons, self.moon_compositions.get_info(),
+                            fraction(self.tidally_locked, self.get_moons()),
+                            fraction(self.subsurface_oceans, self.get_moons())));
+
+        for (name, tally) in &self.traits {
+            s.push_str(&format!("\nOrganism {}\n{}\n", name.to_lowercase(), tally.get_info()));
+        }
+        s.push_str(&format!("\nShares more than {} standard errors from the expected one are marked with !.",
+                            DEVIATION_LIMIT));
+        s
+    }
+}
+
+// Samples the first stars of the galaxy and returns the report, writing the same figures as JSON
+// to the output file
+pub fn run(galaxy: &GalaxySettings, sample: u64, output: &Path) -> Result<String, Box<dyn Error>> {
+    if sample > galaxy.get_size() {
+        return Err(format!("the galaxy holds only {} stars, too few to sample {}; pass a larger --stars \
+                            or a smaller --sample", galaxy.get_size(), sample).into());
+    }
+    let mut stats = Stats::new(galaxy);
+    for index in 0..sample {
+        stats.add_star(index);
+        if (index + 1) % PROGRESS_INTERVAL == 0 {
+            eprintln!("Sampled {} of {} stars.", index + 1, sample);
+        }
+    }
+    fs::write(output, format!("{}\n", serde_json::to_string_pretty(&stats.to_json())?))?;
+    Ok(format!("{}\n\nWrote the figures to {}.", stats.get_info(), output.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::galaxy_generator::GalaxyShape;
+
+    #[test]
+    fn tallies_count_each_value_and_its_share() {
+        let mut tally = Tally::default();
+        assert_eq!(tally.get_fraction("Water"), 0.0);
+        for value in ["Water", "Ammonia", "Water", "None"] {
+            tally.add(value);
+        }
+        assert_eq!(tally.total, 4);
+        assert_eq!(tally.get_count("Water"), 2);
+        assert_eq!(tally.get_count("Methane"), 0);
+        assert_eq!(tally.get_fraction("Ammonia"), 0.25);
+        assert_eq!(tally.to_json()["Water"], json!({ "count": 2, "fraction": 0.5 }));
+    }
+
+    #[test]
+    fn organisms_are_counted_by_trait_and_each_sense_on_its_own() {
+        let mut stats = Stats::new(&GalaxySettings::new(0, 1, GalaxyShape::Disc));
+        stats.add_organism(&[
+            Property::text("Size", "Microscopic"),
+            Property::text("Senses", "Chemoreception, Photoreception"),
+            Property::text("Name", "Cropythis miacatae"),
+        ]);
+        stats.add_organism(&[Property::text("Size", "Small"), Property::text("Senses", "")]);
+
+        assert_eq!(stats.get_species(), 2);
+        assert_eq!(stats.traits["Size"].get_count("Microscopic"), 1);
+        assert_eq!(stats.traits["Senses"].total, 2);
+        assert_eq!(stats.traits["Senses"].get_count("Photoreception"), 1);
+        assert!(!stats.traits.contains_key("Name"));
+    }
+
+    #[test]
+    fn every_sampled_planet_and_moon_is_counted_once() {
+        let galaxy = GalaxySettings::new(0, 20, GalaxyShape::Disc);
+        let mut stats = Stats::new(&galaxy);
+        for index in 0..20 {
+            stats.add_star(index);
+        }
+
+        let planets: Vec<_> = (0..20)
+            .flat_map(|index| PlanetarySystem::generate_native_planets(&galaxy.create_star(index)))
+            .collect();
+        let moons: usize = planets.iter().map(|planet| PlanetaryEnvironment::generate_moons(planet).len()).sum();
+        assert_eq!(stats.get_stars(), 20);
+        assert_eq!(stats.get_planets(), planets.len() as u64);
+        assert_eq!(stats.oceans.total, planets.len() as u64);
+        assert_eq!(stats.classes_by_distance.values().map(|tally| tally.total).sum::<u64>(), planets.len() as u64);
+        assert_eq!(stats.get_moons(), moons as u64);
+        assert_eq!(stats.living, planets.iter().filter(|planet| planet.has_life()).count() as u64);
+        assert_eq!(stats.to_json()["stars"], json!(20));
+    }
+}
@@ -0,0 +1,121 @@
+use std::io;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::widgets::{Block, List, ListState, Paragraph, Row, Table, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::game_manager::{Game, Property};
+
+const LIST_WIDTH: u16 = 38; // in percent of the screen
+const DESCRIPTION_HEIGHT: u16 = 40; // in percent of the detail pane
+const PAGE_LENGTH: usize = 10; // units skipped by Page Up and Page Down
+const KEYS: &str = " ↑↓ select   Enter explore   Backspace leave   b back   q quit";
+
+// Where the player is looking: which unit of the current scene is highlighted, and the last
+// thing that went wrong, if anything
+struct View {
+    list: ListState,
+    status: String,
+}
+
+// Runs the whole game in the terminal, full screen, until the player quits
+pub fn run(game: &mut Game) -> io::Result<()> {
+    let mut terminal = ratatui::try_init()?;
+    let result = run_loop(&mut terminal, game);
+    ratatui::try_restore()?;
+    result
+}
+
+fn run_loop(terminal: &mut DefaultTerminal, game: &mut Game) -> io::Result<()> {
+    let mut view = View { list: ListState::default(), status: String::new() };
+    view.list.select_first();
+
+    loop {
+        terminal.draw(|frame| draw(frame, game, &mut view))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let count = game.get_units().len();
+        let selected = view.list.selected().unwrap_or(0);
+        view.status.clear();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Up | KeyCode::Char('k') => view.list.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down | KeyCode::Char('j') => view.list.select(Some((selected + 1).min(count.saturating_sub(1)))),
+            KeyCode::PageUp => view.list.select(Some(selected.saturating_sub(PAGE_LENGTH))),
+            KeyCode::PageDown => view.list.select(Some((selected + PAGE_LENGTH).min(count.saturating_sub(1)))),
+            KeyCode::Home => view.list.select_first(),
+            KeyCode::End => view.list.select(Some(count.saturating_sub(1))),
+            KeyCode::Enter | KeyCode::Right => match game.step_in(selected) {
+                Ok(_) => view.list.select_first(),
+                Err(error) => view.status = error,
+            },
+            // Coming back out highlights the place just left
+            KeyCode::Backspace | KeyCode::Left => {
+                let left = game.get_path().clone();
+                match game.step_out() {
+                    Ok(_) => view.list.select(game.get_units().iter().position(|unit| *unit.get_path() == left)),
+                    Err(error) => view.status = error,
+                }
+            }
+            KeyCode::Char('b') => match game.back() {
+                Ok(_) => view.list.select_first(),
+                Err(error) => view.status = error,
+            },
+            _ => {}
+        }
+    }
+}
+
+// A breadcrumbs bar across the top, the units of the scene on the left, the highlighted unit on
+// the right, and the keys along the bottom
+fn draw(frame: &mut Frame, game: &Game, view: &mut View) {
+    let [top, middle, bottom] = Layout::vertical([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)])
+        .areas(frame.area());
+    let [left, right] = Layout::horizontal([Constraint::Percentage(LIST_WIDTH), Constraint::Min(0)])
+        .areas(middle);
+    let [upper, lower] = Layout::vertical([Constraint::Percentage(DESCRIPTION_HEIGHT), Constraint::Min(0)])
+        .areas(right);
+
+    frame.render_widget(Paragraph::new(game.get_breadcrumbs()).reversed(), top);
+
+    let scene = game.get_scene();
+    let units = game.get_units();
+    let items: Vec<String> = units.iter()
+        .map(|unit| format!("{:>3} {} {}", unit.get_index(), unit.get_kind(), unit.get_name()))
+        .collect();
+    let list = List::new(items)
+        .block(Block::bordered().title(scene.get_name()))
+        .highlight_style(Style::new().reversed())
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(list, left, &mut view.list);
+
+    // Scenes with nothing further in, like a moon or a single organism, describe themselves
+    let (title, description, properties) = match view.list.selected().and_then(|index| units.get(index)) {
+        Some(unit) => (unit.get_name().clone(), unit.get_description().clone(), unit.get_properties().clone()),
+        None => (scene.get_name(), scene.get_description(), scene.get_properties()),
+    };
+    frame.render_widget(Paragraph::new(description)
+                            .wrap(Wrap { trim: true })
+                            .block(Block::bordered().title(title)), upper);
+    frame.render_widget(properties_table(&properties), lower);
+
+    let status = if view.status.is_empty() { KEYS.to_string() } else { format!(" {}", view.status) };
+    frame.render_widget(Paragraph::new(status).dim(), bottom);
+}
+
+fn properties_table(properties: &[Property]) -> Table<'static> {
+    let width = properties.iter().map(|property| property.get_name().chars().count()).max().unwrap_or(0);
+    let rows: Vec<Row> = properties.iter()
+        .map(|property| Row::new(vec![property.get_name().clone(), property.get_value().to_string()]))
+        .collect();
+    Table::new(rows, [Constraint::Length(width as u16), Constraint::Min(0)])
+        .column_spacing(2)
+        .block(Block::bordered().title("Properties"))
+}